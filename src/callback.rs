@@ -0,0 +1,183 @@
+//!
+//! Call a JS function with typed arguments and return value
+//!
+//! `Callback<Args, Ret>` is deserialized from a JS function. Calling it serializes
+//! `Args` with `to_value`, invokes the function with `undefined` as `this` and
+//! deserializes the returned value into `Ret` with `from_value`.
+//!
+//! `Args` is a tuple of up to eight values, each element is passed as one argument.
+//! `()` calls the function without arguments and a single argument is a one
+//! element tuple, e.g. `(vec![1, 2],)` passes one array.
+//!
+//! ```rust,no_run
+//! # extern crate neon_serde;
+//! # extern crate neon;
+//! # use neon::prelude::*;
+//! use neon_serde::Callback;
+//!
+//! fn label_all(mut cx: FunctionContext) -> JsResult<JsValue> {
+//!     let arg0 = cx.argument::<JsValue>(0)?;
+//!     let arg1 = cx.argument::<JsValue>(1)?;
+//!     let values: Vec<u32> = neon_serde::from_value(&mut cx, arg0)?;
+//!     let label: Callback<(u32, usize), String> = neon_serde::from_value(&mut cx, arg1)?;
+//!
+//!     let mut labels = Vec::new();
+//!     for (i, value) in values.into_iter().enumerate() {
+//!         labels.push(label.call(&mut cx, &(value, i))?);
+//!     }
+//!     let js_value = neon_serde::to_value(&mut cx, &labels)?;
+//!     Ok(js_value)
+//! }
+//! # fn main () {
+//! # }
+//! ```
+//!
+
+use de::from_value;
+use errors::Result as LibResult;
+use neon::prelude::*;
+use raw::RawValue;
+use ser::to_value;
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer, Unexpected};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+mod private {
+    /// Only tuples can be `Arguments`, so other values can not be mistaken for an argument list
+    pub trait Sealed {}
+}
+
+/// The arguments of a `Callback`, a tuple of values implementing `Serialize`
+pub trait Arguments: private::Sealed {
+    /// Serializes each element of the tuple with `to_value`
+    #[doc(hidden)]
+    fn to_js_args<'c, C>(&self, cx: &mut C) -> LibResult<Vec<Handle<'c, JsValue>>>
+    where
+        C: Context<'c>;
+}
+
+impl private::Sealed for () {}
+
+impl Arguments for () {
+    fn to_js_args<'c, C>(&self, _cx: &mut C) -> LibResult<Vec<Handle<'c, JsValue>>>
+    where
+        C: Context<'c>,
+    {
+        Ok(Vec::new())
+    }
+}
+
+macro_rules! impl_arguments {
+    ($(($($name:ident: $ty:ident),+),)*) => {
+        $(
+            impl<$($ty: Serialize),+> private::Sealed for ($($ty,)+) {}
+
+            impl<$($ty: Serialize),+> Arguments for ($($ty,)+) {
+                fn to_js_args<'c, C>(&self, cx: &mut C) -> LibResult<Vec<Handle<'c, JsValue>>>
+                where
+                    C: Context<'c>,
+                {
+                    let ($(ref $name,)+) = *self;
+                    Ok(vec![$(to_value(cx, $name)?),+])
+                }
+            }
+        )*
+    };
+}
+
+impl_arguments! {
+    (t0: T0),
+    (t0: T0, t1: T1),
+    (t0: T0, t1: T1, t2: T2),
+    (t0: T0, t1: T1, t2: T2, t3: T3),
+    (t0: T0, t1: T1, t2: T2, t3: T3, t4: T4),
+    (t0: T0, t1: T1, t2: T2, t3: T3, t4: T4, t5: T5),
+    (t0: T0, t1: T1, t2: T2, t3: T3, t4: T4, t5: T5, t6: T6),
+    (t0: T0, t1: T1, t2: T2, t3: T3, t4: T4, t5: T5, t6: T6, t7: T7),
+}
+
+/// A JS function taking `Args` and returning `Ret`, see the module documentation
+pub struct Callback<'j, Args, Ret> {
+    function: Handle<'j, JsFunction>,
+    types: PhantomData<fn(Args) -> Ret>,
+}
+
+impl<'j, Args, Ret> Callback<'j, Args, Ret> {
+    /// Wraps a JS function
+    pub fn new(function: Handle<'j, JsFunction>) -> Self {
+        Callback {
+            function,
+            types: PhantomData,
+        }
+    }
+
+    /// The wrapped function
+    pub fn function(&self) -> Handle<'j, JsFunction> {
+        self.function
+    }
+}
+
+impl<'j, Args, Ret> Callback<'j, Args, Ret>
+where
+    Args: Arguments,
+    Ret: DeserializeOwned,
+{
+    /// Calls the function with `args` and converts the value it returns
+    ///
+    /// # Errors
+    ///
+    /// When `args` can not be serialized, the function throws
+    /// or its result can not be deserialized into `Ret`
+    ///
+    pub fn call<'c, C>(&self, cx: &mut C, args: &Args) -> LibResult<Ret>
+    where
+        C: Context<'c>,
+    {
+        let args = args.to_js_args(cx)?;
+        let this = JsUndefined::new();
+        let result = self.function.call(cx, this, args)?;
+        from_value(cx, result)
+    }
+}
+
+impl<'j, Args, Ret> Clone for Callback<'j, Args, Ret> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'j, Args, Ret> Copy for Callback<'j, Args, Ret> {}
+
+impl<'j, Args, Ret> fmt::Debug for Callback<'j, Args, Ret> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Callback")
+    }
+}
+
+/// Serialized as the original function
+impl<'j, Args, Ret> Serialize for Callback<'j, Args, Ret> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        RawValue(self.function.upcast()).serialize(serializer)
+    }
+}
+
+/// Borrows the function from the `Deserializer` input the same way `RawValue` does
+impl<'de: 'j, 'j, Args, Ret> Deserialize<'de> for Callback<'j, Args, Ret> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw: RawValue<'j> = RawValue::deserialize(deserializer)?;
+        match raw.0.downcast::<JsFunction>() {
+            Ok(function) => Ok(Callback::new(function)),
+            Err(_) => Err(de::Error::invalid_type(
+                Unexpected::Other("a value that is not a function"),
+                &"a JS function",
+            )),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//!
+//! Convert `SystemTime` to and from a JS `Date`
+//!
+//! `Date` wraps a `SystemTime` and is serialized to a JS `Date`. A plain
+//! `SystemTime` field can be opted in with `#[serde(with = "neon_serde::date")]`.
+//! Both are deserialized from a `Date`, an ISO string or a `Number` of
+//! milliseconds since the epoch. Other serializers see the milliseconds since the epoch.
+//!
+//! ```rust,no_run
+//! # #![allow(dead_code)]
+//! extern crate neon_serde;
+//! #[macro_use]
+//! extern crate serde_derive;
+//!
+//! use std::time::SystemTime;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     created: neon_serde::Date,
+//!     #[serde(with = "neon_serde::date")]
+//!     updated: SystemTime,
+//! }
+//! # fn main () {
+//! # }
+//! ```
+//!
+
+use serde::de::{self, Deserialize, Deserializer, Unexpected, Visitor};
+use serde::{ser, Serialize, Serializer};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name used to recognize a date in `Serializer::serialize_newtype_struct`
+pub(crate) const TOKEN: &str = "$neon_serde::private::Date";
+
+/// The largest distance from the epoch a JS `Date` can hold, in milliseconds
+const MAX_MILLIS: f64 = 8.64e15;
+
+/// True for the ISO format of `Date.prototype.toISOString`, e.g. `2020-01-02T03:04:05.678Z`,
+/// and its shorter forms. A time needs `Z` or an offset, JS would otherwise read it as local time
+pub(crate) fn is_iso_string(text: &str) -> bool {
+    /// Skips `n` ASCII digits
+    fn digits(bytes: &[u8], i: &mut usize, n: usize) -> bool {
+        let end = *i + n;
+        if end > bytes.len() || !bytes[*i..end].iter().all(u8::is_ascii_digit) {
+            return false;
+        }
+        *i = end;
+        true
+    }
+    /// Skips `separator` followed by `n` ASCII digits
+    fn field(bytes: &[u8], i: &mut usize, separator: u8, n: usize) -> bool {
+        if bytes.get(*i) != Some(&separator) {
+            return false;
+        }
+        *i += 1;
+        digits(bytes, i, n)
+    }
+
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    // `YYYY` or an expanded year, e.g. `+002020`
+    let year_len = match bytes.first() {
+        Some(b'+') | Some(b'-') => {
+            i = 1;
+            6
+        }
+        _ => 4,
+    };
+    if !digits(bytes, &mut i, year_len) {
+        return false;
+    }
+    // `-MM` then `-DD`, a date without a time is UTC
+    if bytes.get(i) == Some(&b'-') && !field(bytes, &mut i, b'-', 2) {
+        return false;
+    }
+    if bytes.get(i) == Some(&b'-') && !field(bytes, &mut i, b'-', 2) {
+        return false;
+    }
+    if i == bytes.len() {
+        return true;
+    }
+    // `THH:mm`, then `:ss` and `.sss`
+    if !field(bytes, &mut i, b'T', 2) || !field(bytes, &mut i, b':', 2) {
+        return false;
+    }
+    if bytes.get(i) == Some(&b':') && !field(bytes, &mut i, b':', 2) {
+        return false;
+    }
+    if bytes.get(i) == Some(&b'.') && !field(bytes, &mut i, b'.', 3) {
+        return false;
+    }
+    // `Z` or `+HH:mm`
+    match bytes.get(i) {
+        Some(b'Z') => i += 1,
+        Some(&sign) if sign == b'+' || sign == b'-' => {
+            if !field(bytes, &mut i, sign, 2) || !field(bytes, &mut i, b':', 2) {
+                return false;
+            }
+        }
+        _ => return false,
+    }
+    i == bytes.len()
+}
+
+/// Milliseconds since the epoch, negative before it
+fn to_millis(time: &SystemTime) -> f64 {
+    fn millis(d: Duration) -> f64 {
+        d.as_secs() as f64 * 1000.0 + f64::from(d.subsec_nanos()) / 1_000_000.0
+    }
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => millis(d),
+        Err(e) => -millis(e.duration()),
+    }
+}
+
+/// The time `millis` milliseconds from the epoch, `None` outside of the range of a JS `Date`
+fn from_millis(millis: f64) -> Option<SystemTime> {
+    if !millis.is_finite() || millis.abs() > MAX_MILLIS {
+        return None;
+    }
+    let abs = millis.abs();
+    let secs = (abs / 1000.0).trunc() as u64;
+    let nanos = ((abs % 1000.0) * 1_000_000.0) as u32;
+    let duration = Duration::new(secs, nanos);
+    if millis < 0.0 {
+        UNIX_EPOCH.checked_sub(duration)
+    } else {
+        UNIX_EPOCH.checked_add(duration)
+    }
+}
+
+/// Serializes `time` as a JS `Date` when used with `to_value`
+///
+/// # Errors
+///
+/// When `time` is outside of the range of a JS `Date` or any error from the serializer
+///
+pub fn serialize<S>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let millis = to_millis(time);
+    if millis.abs() > MAX_MILLIS {
+        return Err(ser::Error::custom(format!(
+            "SystemTime out of range for a JS Date: {}ms from the epoch",
+            millis
+        )));
+    }
+    serializer.serialize_newtype_struct(TOKEN, &millis)
+}
+
+/// Deserializes a `SystemTime` from a JS `Date`, an ISO string or epoch milliseconds
+///
+/// # Errors
+///
+/// When the value is not a valid date
+///
+pub fn deserialize<'de, D>(deserializer: D) -> Result<SystemTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(TOKEN, DateVisitor)
+}
+
+/// A `SystemTime` that is a JS `Date` on the node side
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date(pub SystemTime);
+
+impl From<SystemTime> for Date {
+    fn from(time: SystemTime) -> Self {
+        Date(time)
+    }
+}
+
+impl From<Date> for SystemTime {
+    fn from(date: Date) -> Self {
+        date.0
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer).map(Date)
+    }
+}
+
+struct DateVisitor;
+
+impl<'de> Visitor<'de> for DateVisitor {
+    type Value = SystemTime;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a Date, an ISO string or milliseconds since the epoch")
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        from_millis(v).ok_or_else(|| E::invalid_value(Unexpected::Float(v), &self))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_f64(v as f64)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_f64(v as f64)
+    }
+
+    /// other deserializers see the milliseconds written by `serialize`
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_f64(self)
+    }
+}
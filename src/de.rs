@@ -2,17 +2,74 @@
 //! Deserialize a `JsValue` into a Rust data structure
 //!
 
+use date;
 use errors::Error as LibError;
 use errors::ErrorKind;
 use errors::Result as LibResult;
+use js;
 use neon::prelude::*;
+use patch;
+use raw;
 use serde;
 use serde::de::Visitor;
-use serde::de::{DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, Unexpected,
-                VariantAccess};
+use serde::de::{Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+                SeqAccess, Unexpected, VariantAccess};
+use std::cell::Cell;
+use typed_array;
+
+/// The largest integer a JS `Number` can represent exactly, `Number.MAX_SAFE_INTEGER`
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991f64;
+
+/// Options changing how values are converted by `from_value_with`
+///
+/// The same options are used for every nested value, including
+/// array elements, object properties and enum variants
+#[derive(Clone, Debug, Default)]
+pub struct DeserializerOptions {
+    strict_integers: bool,
+    distinguish_null: bool,
+    call_to_json: bool,
+}
+
+impl DeserializerOptions {
+    /// Options matching the behaviour of `from_value`
+    pub fn new() -> Self {
+        DeserializerOptions::default()
+    }
+
+    /// When set a `Number` holding an integer outside of `Number.MAX_SAFE_INTEGER`
+    /// is an `UnsafeInteger` error instead of a possibly rounded value
+    pub fn strict_integers(mut self, strict: bool) -> Self {
+        self.strict_integers = strict;
+        self
+    }
+
+    /// When set `undefined` or a missing property is the outer `None` of an
+    /// `Option<Option<T>>` and `null` is `Some(None)`
+    ///
+    /// With this option a plain `Option<T>` only accepts `undefined` as `None`,
+    /// `Patch<T>` tells the three states apart with or without this option
+    pub fn distinguish_null(mut self, distinguish: bool) -> Self {
+        self.distinguish_null = distinguish;
+        self
+    }
+
+    /// When set an object with a `toJSON` method is replaced by the result of
+    /// calling it before being deserialized, the same way `JSON.stringify` does,
+    /// e.g. a `Date` is deserialized from its ISO string
+    ///
+    /// `Buffer`s are still deserialized as bytes and a `RawValue` holds the original value
+    pub fn call_to_json(mut self, call: bool) -> Self {
+        self.call_to_json = call;
+        self
+    }
+}
 
 /// Deserialize an instance of type `T` from a `Handle<JsValue>`
 ///
+/// `T` can hold handles borrowed from `value`, e.g. a `RawValue<'j>`,
+/// they can not outlive the context `value` belongs to
+///
 /// # Errors
 ///
 /// Can fail for various reasons see `ErrorKind`
@@ -20,9 +77,28 @@ use serde::de::{DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAcc
 pub fn from_value<'j, C, T>(cx: &mut C, value: Handle<'j, JsValue>) -> LibResult<T>
 where
     C: Context<'j>,
-    T: DeserializeOwned + ?Sized,
+    T: Deserialize<'j> + ?Sized,
+{
+    from_value_with(cx, value, &DeserializerOptions::default())
+}
+
+/// Deserialize an instance of type `T` from a `Handle<JsValue>` using the given options
+///
+/// # Errors
+///
+/// Can fail for various reasons see `ErrorKind`
+///
+pub fn from_value_with<'j, C, T>(
+    cx: &mut C,
+    value: Handle<'j, JsValue>,
+    opts: &DeserializerOptions,
+) -> LibResult<T>
+where
+    C: Context<'j>,
+    T: Deserialize<'j> + ?Sized,
 {
-    let mut deserializer: Deserializer<C> = Deserializer::new(cx, value);
+    let shared = Shared::new(opts);
+    let mut deserializer: Deserializer<C> = Deserializer::new(cx, &shared, value);
     let t = T::deserialize(&mut deserializer)?;
     Ok(t)
 }
@@ -30,33 +106,213 @@ where
 pub fn from_value_opt<'j, C, T>(cx: &mut C, value: Option<Handle<'j, JsValue>>) -> LibResult<T>
 where
     C: Context<'j>,
-    T: DeserializeOwned + ?Sized,
+    T: Deserialize<'j> + ?Sized,
+{
+    from_value_opt_with(cx, value, &DeserializerOptions::default())
+}
+
+/// Same as `from_value_opt` using the given options
+pub fn from_value_opt_with<'j, C, T>(
+    cx: &mut C,
+    value: Option<Handle<'j, JsValue>>,
+    opts: &DeserializerOptions,
+) -> LibResult<T>
+where
+    C: Context<'j>,
+    T: Deserialize<'j> + ?Sized,
 {
     let unwrapped = value.unwrap_or_else(|| JsUndefined::new().upcast());
-    from_value(cx, unwrapped)
+    from_value_with(cx, unwrapped, opts)
+}
+
+/// What every `Deserializer` of one `from_value_with` call shares, the built-in
+/// classes are looked up the first time an object needs them and then reused
+#[doc(hidden)]
+struct Shared<'j> {
+    opts: DeserializerOptions,
+    builtins: Cell<Option<js::Builtins<'j>>>,
+}
+
+#[doc(hidden)]
+impl<'j> Shared<'j> {
+    fn new(opts: &DeserializerOptions) -> Self {
+        Shared {
+            opts: opts.clone(),
+            builtins: Cell::new(None),
+        }
+    }
 }
 
 #[doc(hidden)]
 pub struct Deserializer<'a, 'j, C: Context<'j> + 'a> {
     cx: &'a mut C,
+    shared: &'a Shared<'j>,
     input: Handle<'j, JsValue>,
+    /// `input` before `call_to_json` replaced it, a `RawValue` is given this handle
+    original: Handle<'j, JsValue>,
+    /// set until `call_to_json` was applied to `input`
+    to_json_pending: bool,
+    /// set once an outer `Option` took `null` as `Some` with `distinguish_null`
+    null_is_some: bool,
 }
 
 #[doc(hidden)]
 impl<'a, 'j, C: Context<'j>> Deserializer<'a, 'j, C> {
-    fn new(cx: &'a mut C, input: Handle<'j, JsValue>) -> Self {
-        Deserializer { cx, input }
+    fn new(cx: &'a mut C, shared: &'a Shared<'j>, input: Handle<'j, JsValue>) -> Self {
+        Deserializer {
+            cx,
+            shared,
+            input,
+            original: input,
+            to_json_pending: shared.opts.call_to_json,
+            null_is_some: false,
+        }
     }
 }
 
 #[doc(hidden)]
-impl<'x, 'd, 'a, 'j, C: Context<'j>> serde::de::Deserializer<'x> for &'d mut Deserializer<'a, 'j, C> {
+impl<'a, 'j, C: Context<'j>> Deserializer<'a, 'j, C> {
+    /// Replaces `input` by the result of its `toJSON` method with `call_to_json`,
+    /// this is done before `input` is first read so a `RawValue` gets the original
+    fn apply_to_json(&mut self) -> LibResult<()> {
+        if self.to_json_pending {
+            self.to_json_pending = false;
+            self.input = js::call_to_json(self.cx, self.input)?;
+        }
+        Ok(())
+    }
+
+    /// The class of `input` when it is a `Map`, `Set`, `DataView` or typed array
+    fn builtin_class(&mut self) -> LibResult<Option<&'static str>> {
+        if !self.input.is_a::<JsObject>() {
+            return Ok(None);
+        }
+        let builtins = match self.shared.builtins.get() {
+            Some(builtins) => builtins,
+            None => {
+                let builtins = js::Builtins::new(self.cx)?;
+                self.shared.builtins.set(Some(builtins));
+                builtins
+            }
+        };
+        builtins.class_of(self.cx, self.input)
+    }
+
+    /// Copies the bytes of a `Buffer`, `ArrayBuffer`, typed array or `DataView`,
+    /// only the part of the `ArrayBuffer` covered by a view is copied
+    fn copy_bytes(&mut self) -> LibResult<Vec<u8>> {
+        if let Ok(buff) = self.input.downcast::<JsArrayBuffer>() {
+            return Ok(self.cx.borrow(&buff, |buff| Vec::from(buff.as_slice())));
+        }
+        if !self.input.is_a::<JsBuffer>() {
+            if let Ok(view) = self.input.downcast::<JsObject>() {
+                match self.builtin_class()? {
+                    Some(class) if class == "DataView" || is_typed_array(class) => {
+                        return js::copy_view_bytes(self.cx, view);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let buff = self.input.downcast::<JsBuffer>().or_throw(self.cx)?;
+        let copy = self.cx.borrow(&buff, |buff| Vec::from(buff.as_slice()));
+        Ok(copy)
+    }
+
+    /// Visits the elements of a typed array, they are copied from the
+    /// `ArrayBuffer` at once instead of read one property at a time
+    fn deserialize_typed_array<'x, V>(&mut self, class: &str, visitor: V) -> LibResult<V::Value>
+    where
+        V: Visitor<'x>,
+    {
+        let view = self.input.downcast::<JsObject>().or_throw(self.cx)?;
+        let bytes = js::copy_view_bytes(self.cx, view)?;
+        match class {
+            "Int8Array" => visitor.visit_seq(TypedArrayAccess::<i8>::new(&bytes)),
+            "Uint8Array" | "Uint8ClampedArray" => {
+                visitor.visit_seq(TypedArrayAccess::<u8>::new(&bytes))
+            }
+            "Int16Array" => visitor.visit_seq(TypedArrayAccess::<i16>::new(&bytes)),
+            "Uint16Array" => visitor.visit_seq(TypedArrayAccess::<u16>::new(&bytes)),
+            "Int32Array" => visitor.visit_seq(TypedArrayAccess::<i32>::new(&bytes)),
+            "Uint32Array" => visitor.visit_seq(TypedArrayAccess::<u32>::new(&bytes)),
+            "Float32Array" => visitor.visit_seq(TypedArrayAccess::<f32>::new(&bytes)),
+            "Float64Array" => visitor.visit_seq(TypedArrayAccess::<f64>::new(&bytes)),
+            "BigInt64Array" => visitor.visit_seq(TypedArrayAccess::<i64>::new(&bytes)),
+            "BigUint64Array" => visitor.visit_seq(TypedArrayAccess::<u64>::new(&bytes)),
+            _ => bail!(ErrorKind::NotImplemented("unsupported typed array")),
+        }
+    }
+
+    /// Visits a `BigInt` with the smallest integer type that can hold it,
+    /// the visitor is responsible for checking it fits the target type
+    fn deserialize_bigint<'x, V>(&mut self, visitor: V) -> LibResult<V::Value>
+    where
+        V: Visitor<'x>,
+    {
+        let decimal = js::to_js_string(self.cx, self.input)?;
+        if let Ok(v) = decimal.parse::<i64>() {
+            visitor.visit_i64(v)
+        } else if let Ok(v) = decimal.parse::<u64>() {
+            visitor.visit_u64(v)
+        } else if let Ok(v) = decimal.parse::<i128>() {
+            visitor.visit_i128(v)
+        } else if let Ok(v) = decimal.parse::<u128>() {
+            visitor.visit_u128(v)
+        } else {
+            bail!(ErrorKind::BigIntOutOfRange(decimal))
+        }
+    }
+
+    /// Visits the milliseconds since the epoch of a `Date`, an ISO string
+    /// or a `Number`, strings are checked to be ISO before the JS `Date`
+    /// constructor parses them
+    fn deserialize_date<'x, V>(&mut self, visitor: V) -> LibResult<V::Value>
+    where
+        V: Visitor<'x>,
+    {
+        if let Ok(val) = self.input.downcast::<JsNumber>() {
+            return visitor.visit_f64(val.value());
+        }
+        if let Ok(val) = self.input.downcast::<JsString>() {
+            let text = val.value();
+            if !date::is_iso_string(&text) {
+                return Err(serde::de::Error::invalid_value(
+                    Unexpected::Str(&text),
+                    &visitor,
+                ));
+            }
+        }
+        let class = js::class_name(self.cx, self.input)?;
+        let js_date = match class.as_str() {
+            "Date" => self.input.downcast::<JsObject>().or_throw(self.cx)?,
+            "String" if self.input.is_a::<JsString>() => {
+                js::construct_global(self.cx, "Date", vec![self.input])?
+            }
+            _ => {
+                return Err(serde::de::Error::invalid_type(
+                    Unexpected::Other(&class),
+                    &visitor,
+                ))
+            }
+        };
+        let millis = js::call_method(self.cx, js_date, "getTime", vec![])?
+            .downcast::<JsNumber>()
+            .or_throw(self.cx)?
+            .value();
+        visitor.visit_f64(millis)
+    }
+}
+
+#[doc(hidden)]
+impl<'d, 'a, 'j, C: Context<'j>> serde::de::Deserializer<'j> for &'d mut Deserializer<'a, 'j, C> {
     type Error = LibError;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
+        self.apply_to_json()?;
         if self.input.downcast::<JsNull>().is_ok() || self.input.downcast::<JsUndefined>().is_ok() {
             visitor.visit_unit()
         } else if let Ok(val) = self.input.downcast::<JsBoolean>() {
@@ -65,19 +321,45 @@ impl<'x, 'd, 'a, 'j, C: Context<'j>> serde::de::Deserializer<'x> for &'d mut Des
             visitor.visit_string(val.value())
         } else if let Ok(val) = self.input.downcast::<JsNumber>() {
             let v = val.value();
-            if v.trunc() == v {
+            let is_integer = v.is_finite() && v.trunc() == v;
+            if self.shared.opts.strict_integers && is_integer && v.abs() > MAX_SAFE_INTEGER {
+                bail!(ErrorKind::UnsafeInteger(v.to_string()))
+            } else if is_integer {
                 visitor.visit_i64(v as i64)
             } else {
                 visitor.visit_f64(v)
             }
-        } else if let Ok(_val) = self.input.downcast::<JsBuffer>() {
-            self.deserialize_bytes(visitor)
         } else if let Ok(val) = self.input.downcast::<JsArray>() {
-            let mut deserializer = JsArrayAccess::new(self.cx, val);
+            let mut deserializer = JsArrayAccess::new(self.cx, self.shared, val);
             visitor.visit_seq(&mut deserializer)
+        } else if self.input.is_a::<JsArrayBuffer>() {
+            let copy = self.copy_bytes()?;
+            visitor.visit_byte_buf(copy)
         } else if let Ok(val) = self.input.downcast::<JsObject>() {
-            let mut deserializer = JsObjectAccess::new(self.cx, val)?;
-            visitor.visit_map(&mut deserializer)
+            match self.builtin_class()? {
+                Some("Map") => {
+                    let entries = js::array_from(self.cx, self.input)?;
+                    let mut deserializer = JsEntriesAccess::new(self.cx, self.shared, entries);
+                    visitor.visit_map(&mut deserializer)
+                }
+                Some("Set") => {
+                    let values = js::array_from(self.cx, self.input)?;
+                    let mut deserializer = JsArrayAccess::new(self.cx, self.shared, values);
+                    visitor.visit_seq(&mut deserializer)
+                }
+                // a `Buffer` is a `Uint8Array`, `deserialize_seq` reads either as numbers
+                Some("Uint8Array") | Some("DataView") => {
+                    let copy = self.copy_bytes()?;
+                    visitor.visit_byte_buf(copy)
+                }
+                Some(class) => self.deserialize_typed_array(class, visitor),
+                None => {
+                    let mut deserializer = JsObjectAccess::new(self.cx, self.shared, val)?;
+                    visitor.visit_map(&mut deserializer)
+                }
+            }
+        } else if js::class_name(self.cx, self.input)? == "BigInt" {
+            self.deserialize_bigint(visitor)
         } else {
             bail!(ErrorKind::NotImplemented(
                 "unimplemented Deserializer::Deserializer",
@@ -87,26 +369,136 @@ impl<'x, 'd, 'a, 'j, C: Context<'j>> serde::de::Deserializer<'x> for &'d mut Des
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
-        if self.input.downcast::<JsNull>().is_ok() || self.input.downcast::<JsUndefined>().is_ok() {
+        self.apply_to_json()?;
+        let is_null = self.input.is_a::<JsNull>();
+        if is_null && self.shared.opts.distinguish_null && !self.null_is_some {
+            self.null_is_some = true;
+            visitor.visit_some(self)
+        } else if is_null || self.input.is_a::<JsUndefined>() {
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
         }
     }
 
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'j>,
+    {
+        self.apply_to_json()?;
+        match self.input.downcast::<JsArray>() {
+            Ok(val) => {
+                let mut deserializer = JsEntriesAccess::new(self.cx, self.shared, val);
+                visitor.visit_map(&mut deserializer)
+            }
+            Err(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Reads the own properties of an object without looking up its class,
+    /// only `deserialize_any` needs to tell a `Map` or `Set` from a plain object
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'j>,
+    {
+        self.apply_to_json()?;
+        if self.input.is_a::<JsArray>() || self.input.is_a::<JsBuffer>() {
+            return self.deserialize_any(visitor);
+        }
+        match self.input.downcast::<JsObject>() {
+            Ok(val) => {
+                let mut deserializer = JsObjectAccess::new(self.cx, self.shared, val)?;
+                visitor.visit_map(&mut deserializer)
+            }
+            Err(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'j>,
+    {
+        if name == raw::TOKEN {
+            let previous = raw::put(Some(self.original));
+            let result = visitor.visit_unit();
+            raw::put(previous);
+            return result;
+        }
+        self.apply_to_json()?;
+        if name == patch::TOKEN {
+            return if self.input.is_a::<JsUndefined>() {
+                visitor.visit_none()
+            } else if self.input.is_a::<JsNull>() {
+                visitor.visit_unit()
+            } else {
+                visitor.visit_some(self)
+            };
+        }
+        if name == date::TOKEN {
+            return self.deserialize_date(visitor);
+        }
+        let expected_class = match typed_array::class_for_token(name) {
+            Some(class) => class,
+            None => return self.deserialize_any(visitor),
+        };
+        if self.input.is_a::<JsArray>() {
+            return self.deserialize_any(visitor);
+        }
+        if self.builtin_class()? != Some(expected_class) {
+            let class = js::class_name(self.cx, self.input)?;
+            return Err(serde::de::Error::invalid_type(
+                Unexpected::Other(&class),
+                &expected_class,
+            ));
+        }
+        let view = self.input.downcast::<JsObject>().or_throw(self.cx)?;
+        let bytes = js::copy_view_bytes(self.cx, view)?;
+        visitor.visit_byte_buf(bytes)
+    }
+
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
+        self.apply_to_json()?;
         if let Ok(val) = self.input.downcast::<JsString>() {
-            visitor.visit_enum(JsEnumAccess::new(self.cx, val.value(), None))
+            visitor.visit_enum(JsEnumAccess::new(self.cx, self.shared, val.value(), None))
+        } else if let Ok(val) = self.input.downcast::<JsNumber>() {
+            // a unit variant serialized as its index
+            let index = val.value();
+            let variant = if index.trunc() == index && index >= 0. {
+                variants.get(index as usize)
+            } else {
+                None
+            };
+            match variant {
+                Some(variant) => visitor.visit_enum(JsEnumAccess::new(
+                    self.cx,
+                    self.shared,
+                    (*variant).to_owned(),
+                    None,
+                )),
+                None => Err(serde::de::Error::invalid_value(
+                    Unexpected::Float(index),
+                    &"a variant index",
+                )),
+            }
         } else if let Ok(val) = self.input.downcast::<JsObject>() {
             let prop_names = val.get_own_property_names(self.cx)?;
             let len = prop_names.len();
@@ -118,49 +510,136 @@ impl<'x, 'd, 'a, 'j, C: Context<'j>> serde::de::Deserializer<'x> for &'d mut Des
             }
             let key = prop_names.get(self.cx, 0)?.downcast::<JsString>().or_throw(self.cx)?;
             let enum_value = val.get(self.cx, key)?;
-            visitor.visit_enum(JsEnumAccess::new(self.cx, key.value(), Some(enum_value)))
+            visitor.visit_enum(JsEnumAccess::new(
+                self.cx,
+                self.shared,
+                key.value(),
+                Some(enum_value),
+            ))
         } else {
             let m = self.input.to_string(self.cx)?.value();
             Err(ErrorKind::InvalidKeyType(m))?
         }
     }
 
+    /// Typed arrays, a `Buffer` included, are copied at once and visited as numbers
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'j>,
+    {
+        self.apply_to_json()?;
+        if !self.input.is_a::<JsArray>() {
+            if let Some(class) = self.builtin_class()? {
+                if is_typed_array(class) {
+                    return self.deserialize_typed_array(class, visitor);
+                }
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'j>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
-        let buff = self.input.downcast::<JsBuffer>().or_throw(self.cx)?;
-        let copy = self.cx.borrow(&buff, |buff| Vec::from(buff.as_slice()));
+        self.apply_to_json()?;
+        let copy = self.copy_bytes()?;
         visitor.visit_bytes(&copy)
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
-        let buff = self.input.downcast::<JsBuffer>().or_throw(self.cx)?;
-        let copy = self.cx.borrow(&buff, |buff| Vec::from(buff.as_slice()));
+        self.apply_to_json()?;
+        let copy = self.copy_bytes()?;
         visitor.visit_byte_buf(copy)
     }
 
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'j>,
+    {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'j>,
+    {
+        self.apply_to_json()?;
+        match self.input.downcast::<JsNumber>() {
+            Ok(val) => visitor.visit_f64(val.value()),
+            Err(_) => self.deserialize_any(visitor),
+        }
+    }
+
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
         visitor.visit_unit()
     }
 
     forward_to_deserialize_any! {
-       <V: Visitor<'x>>
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        unit unit_struct seq tuple tuple_struct map struct identifier
-        newtype_struct
+       <V: Visitor<'j>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        unit unit_struct tuple_struct identifier
+    }
+}
+
+/// True for the class of any typed array, e.g. `Float64Array`
+fn is_typed_array(class: &str) -> bool {
+    class != "Array" && class.ends_with("Array")
+}
+
+#[doc(hidden)]
+struct TypedArrayAccess<T> {
+    values: ::std::vec::IntoIter<T>,
+}
+
+#[doc(hidden)]
+impl<T: typed_array::Element> TypedArrayAccess<T> {
+    fn new(bytes: &[u8]) -> Self {
+        TypedArrayAccess {
+            values: typed_array::decode(bytes).into_iter(),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl<'x, T> SeqAccess<'x> for TypedArrayAccess<T>
+where
+    T: IntoDeserializer<'x, LibError>,
+{
+    type Error = LibError;
+
+    fn next_element_seed<S>(&mut self, seed: S) -> LibResult<Option<S::Value>>
+    where
+        S: DeserializeSeed<'x>,
+    {
+        match self.values.next() {
+            Some(value) => seed.deserialize(value.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
     }
 }
 
 #[doc(hidden)]
 struct JsArrayAccess<'a, 'j, C: Context<'j> + 'a> {
     cx: &'a mut C,
+    shared: &'a Shared<'j>,
     input: Handle<'j, JsArray>,
     idx: u32,
     len: u32,
@@ -168,9 +647,10 @@ struct JsArrayAccess<'a, 'j, C: Context<'j> + 'a> {
 
 #[doc(hidden)]
 impl<'a, 'j, C: Context<'j>> JsArrayAccess<'a, 'j, C> {
-    fn new(cx: &'a mut C, input: Handle<'j, JsArray>) -> Self {
+    fn new(cx: &'a mut C, shared: &'a Shared<'j>, input: Handle<'j, JsArray>) -> Self {
         JsArrayAccess {
             cx,
+            shared,
             input,
             idx: 0,
             len: input.len(),
@@ -179,12 +659,12 @@ impl<'a, 'j, C: Context<'j>> JsArrayAccess<'a, 'j, C> {
 }
 
 #[doc(hidden)]
-impl<'x, 'a, 'j, C: Context<'j>> SeqAccess<'x> for JsArrayAccess<'a, 'j, C> {
+impl<'a, 'j, C: Context<'j>> SeqAccess<'j> for JsArrayAccess<'a, 'j, C> {
     type Error = LibError;
 
     fn next_element_seed<T>(&mut self, seed: T) -> LibResult<Option<T::Value>>
     where
-        T: DeserializeSeed<'x>,
+        T: DeserializeSeed<'j>,
     {
         if self.idx >= self.len {
             return Ok(None);
@@ -192,7 +672,7 @@ impl<'x, 'a, 'j, C: Context<'j>> SeqAccess<'x> for JsArrayAccess<'a, 'j, C> {
         let v = self.input.get(self.cx, self.idx)?;
         self.idx += 1;
 
-        let mut de = Deserializer::new(self.cx, v);
+        let mut de = Deserializer::new(self.cx, self.shared, v);
         seed.deserialize(&mut de).map(Some)
     }
 }
@@ -200,6 +680,7 @@ impl<'x, 'a, 'j, C: Context<'j>> SeqAccess<'x> for JsArrayAccess<'a, 'j, C> {
 #[doc(hidden)]
 struct JsObjectAccess<'a, 'j, C: Context<'j> + 'a> {
     cx: &'a mut C,
+    shared: &'a Shared<'j>,
     input: Handle<'j, JsObject>,
     prop_names: Handle<'j, JsArray>,
     idx: u32,
@@ -207,13 +688,18 @@ struct JsObjectAccess<'a, 'j, C: Context<'j> + 'a> {
 }
 
 #[doc(hidden)]
-impl<'x, 'a, 'j, C: Context<'j>> JsObjectAccess<'a, 'j, C> {
-    fn new(cx: &'a mut C, input: Handle<'j, JsObject>) -> LibResult<Self> {
+impl<'a, 'j, C: Context<'j>> JsObjectAccess<'a, 'j, C> {
+    fn new(
+        cx: &'a mut C,
+        shared: &'a Shared<'j>,
+        input: Handle<'j, JsObject>,
+    ) -> LibResult<Self> {
         let prop_names = input.get_own_property_names(cx)?;
         let len = prop_names.len();
 
         Ok(JsObjectAccess {
             cx,
+            shared,
             input,
             prop_names,
             idx: 0,
@@ -223,26 +709,26 @@ impl<'x, 'a, 'j, C: Context<'j>> JsObjectAccess<'a, 'j, C> {
 }
 
 #[doc(hidden)]
-impl<'x, 'a, 'j, C: Context<'j>> MapAccess<'x> for JsObjectAccess<'a, 'j, C> {
+impl<'a, 'j, C: Context<'j>> MapAccess<'j> for JsObjectAccess<'a, 'j, C> {
     type Error = LibError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
-        K: DeserializeSeed<'x>,
+        K: DeserializeSeed<'j>,
     {
         if self.idx >= self.len {
             return Ok(None);
         }
 
         let prop_name = self.prop_names.get(self.cx, self.idx)?;
+        let key = prop_name.to_string(self.cx)?.value();
 
-        let mut de = Deserializer::new(self.cx, prop_name);
-        seed.deserialize(&mut de).map(Some)
+        seed.deserialize(MapKeyDeserializer::new(key)).map(Some)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
-        V: DeserializeSeed<'x>,
+        V: DeserializeSeed<'j>,
     {
         if self.idx >= self.len {
             return Err(ErrorKind::ArrayIndexOutOfBounds(self.len, self.idx))?;
@@ -251,24 +737,199 @@ impl<'x, 'a, 'j, C: Context<'j>> MapAccess<'x> for JsObjectAccess<'a, 'j, C> {
         let value = self.input.get(self.cx, prop_name)?;
 
         self.idx += 1;
-        let mut de = Deserializer::new(self.cx, value);
+        let mut de = Deserializer::new(self.cx, self.shared, value);
         let res = seed.deserialize(&mut de)?;
         Ok(res)
     }
 }
 
+/// Implements a `deserialize_*` method by parsing the key with `str::parse`
+macro_rules! deserialize_parsed_key {
+    (<$de:lifetime> $($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<$de>,
+            {
+                match self.key.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(_) => Err(serde::de::Error::invalid_type(
+                        Unexpected::Str(&self.key),
+                        &visitor,
+                    )),
+                }
+            }
+        )*
+    };
+}
+
+/// Deserializes an object property name, property names are always strings
+/// in JS so they are parsed when a number, bool or char key is expected
+#[doc(hidden)]
+struct MapKeyDeserializer {
+    key: String,
+}
+
+#[doc(hidden)]
+impl MapKeyDeserializer {
+    fn new(key: String) -> Self {
+        MapKeyDeserializer { key }
+    }
+}
+
+#[doc(hidden)]
+impl<'x> serde::de::Deserializer<'x> for MapKeyDeserializer {
+    type Error = LibError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'x>,
+    {
+        visitor.visit_string(self.key)
+    }
+
+    deserialize_parsed_key! {
+        <'x>
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'x>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'x>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'x>,
+    {
+        visitor.visit_enum(self.key.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+       <V: Visitor<'x>>
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+#[doc(hidden)]
+struct JsEntriesAccess<'a, 'j, C: Context<'j> + 'a> {
+    cx: &'a mut C,
+    shared: &'a Shared<'j>,
+    entries: Handle<'j, JsArray>,
+    idx: u32,
+    len: u32,
+}
+
+#[doc(hidden)]
+impl<'a, 'j, C: Context<'j>> JsEntriesAccess<'a, 'j, C> {
+    fn new(cx: &'a mut C, shared: &'a Shared<'j>, entries: Handle<'j, JsArray>) -> Self {
+        JsEntriesAccess {
+            cx,
+            shared,
+            entries,
+            idx: 0,
+            len: entries.len(),
+        }
+    }
+
+    /// Gets half of the current `[key, value]` entry
+    fn entry_part(&mut self, part: u32) -> LibResult<Handle<'j, JsValue>> {
+        if self.idx >= self.len {
+            return Err(ErrorKind::ArrayIndexOutOfBounds(self.len, self.idx))?;
+        }
+        let entry = self.entries.get(self.cx, self.idx)?;
+        let entry = entry.downcast::<JsArray>().or_throw(self.cx)?;
+        if entry.len() != 2 {
+            return Err(serde::de::Error::invalid_length(
+                entry.len() as usize,
+                &"a [key, value] entry",
+            ));
+        }
+        let value = entry.get(self.cx, part)?;
+        Ok(value)
+    }
+}
+
+#[doc(hidden)]
+impl<'a, 'j, C: Context<'j>> MapAccess<'j> for JsEntriesAccess<'a, 'j, C> {
+    type Error = LibError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'j>,
+    {
+        if self.idx >= self.len {
+            return Ok(None);
+        }
+        let key = self.entry_part(0)?;
+
+        let mut de = Deserializer::new(self.cx, self.shared, key);
+        seed.deserialize(&mut de).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'j>,
+    {
+        let value = self.entry_part(1)?;
+
+        self.idx += 1;
+        let mut de = Deserializer::new(self.cx, self.shared, value);
+        seed.deserialize(&mut de)
+    }
+}
+
 #[doc(hidden)]
 struct JsEnumAccess<'a, 'j, C: Context<'j> + 'a> {
     cx: &'a mut C,
+    shared: &'a Shared<'j>,
     variant: String,
     value: Option<Handle<'j, JsValue>>,
 }
 
 #[doc(hidden)]
 impl<'a, 'j, C: Context<'j>> JsEnumAccess<'a, 'j, C> {
-    fn new(cx: &'a mut C, key: String, value: Option<Handle<'j, JsValue>>) -> Self {
+    fn new(
+        cx: &'a mut C,
+        shared: &'a Shared<'j>,
+        key: String,
+        value: Option<Handle<'j, JsValue>>,
+    ) -> Self {
         JsEnumAccess {
             cx,
+            shared,
             variant: key,
             value,
         }
@@ -276,17 +937,16 @@ impl<'a, 'j, C: Context<'j>> JsEnumAccess<'a, 'j, C> {
 }
 
 #[doc(hidden)]
-impl<'x, 'a, 'j, C: Context<'j>> EnumAccess<'x> for JsEnumAccess<'a, 'j, C> {
+impl<'a, 'j, C: Context<'j>> EnumAccess<'j> for JsEnumAccess<'a, 'j, C> {
     type Error = LibError;
     type Variant = JsVariantAccess<'a, 'j, C>;
 
     fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
     where
-        V: DeserializeSeed<'x>,
+        V: DeserializeSeed<'j>,
     {
-        use serde::de::IntoDeserializer;
         let variant = self.variant.into_deserializer();
-        let variant_access = JsVariantAccess::new(self.cx, self.value);
+        let variant_access = JsVariantAccess::new(self.cx, self.shared, self.value);
         seed.deserialize(variant).map(|v| (v, variant_access))
     }
 }
@@ -294,24 +954,29 @@ impl<'x, 'a, 'j, C: Context<'j>> EnumAccess<'x> for JsEnumAccess<'a, 'j, C> {
 #[doc(hidden)]
 struct JsVariantAccess<'a, 'j, C: Context<'j> + 'a> {
     cx: &'a mut C,
+    shared: &'a Shared<'j>,
     value: Option<Handle<'j, JsValue>>,
 }
 
 #[doc(hidden)]
 impl<'a, 'j, C: Context<'j>> JsVariantAccess<'a, 'j, C> {
-    fn new(cx: &'a mut C, value: Option<Handle<'j, JsValue>>) -> Self {
-        JsVariantAccess { cx, value }
+    fn new(
+        cx: &'a mut C,
+        shared: &'a Shared<'j>,
+        value: Option<Handle<'j, JsValue>>,
+    ) -> Self {
+        JsVariantAccess { cx, shared, value }
     }
 }
 
 #[doc(hidden)]
-impl<'x, 'a, 'j, C: Context<'j>> VariantAccess<'x> for JsVariantAccess<'a, 'j, C> {
+impl<'a, 'j, C: Context<'j>> VariantAccess<'j> for JsVariantAccess<'a, 'j, C> {
     type Error = LibError;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
         match self.value {
             Some(val) => {
-                let mut deserializer = Deserializer::new(self.cx, val);
+                let mut deserializer = Deserializer::new(self.cx, self.shared, val);
                 serde::de::Deserialize::deserialize(&mut deserializer)
             }
             None => Ok(()),
@@ -320,11 +985,11 @@ impl<'x, 'a, 'j, C: Context<'j>> VariantAccess<'x> for JsVariantAccess<'a, 'j, C
 
     fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
     where
-        T: DeserializeSeed<'x>,
+        T: DeserializeSeed<'j>,
     {
         match self.value {
             Some(val) => {
-                let mut deserializer = Deserializer::new(self.cx, val);
+                let mut deserializer = Deserializer::new(self.cx, self.shared, val);
                 seed.deserialize(&mut deserializer)
             }
             None => Err(serde::de::Error::invalid_type(
@@ -336,12 +1001,12 @@ impl<'x, 'a, 'j, C: Context<'j>> VariantAccess<'x> for JsVariantAccess<'a, 'j, C
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
         match self.value {
             Some(handle) => {
                 if let Ok(val) = handle.downcast::<JsArray>() {
-                    let mut deserializer = JsArrayAccess::new(self.cx, val);
+                    let mut deserializer = JsArrayAccess::new(self.cx, self.shared, val);
                     visitor.visit_seq(&mut deserializer)
                 } else {
                     Err(serde::de::Error::invalid_type(
@@ -363,12 +1028,12 @@ impl<'x, 'a, 'j, C: Context<'j>> VariantAccess<'x> for JsVariantAccess<'a, 'j, C
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'x>,
+        V: Visitor<'j>,
     {
         match self.value {
             Some(handle) => {
                 if let Ok(val) = handle.downcast::<JsObject>() {
-                    let mut deserializer = JsObjectAccess::new(self.cx, val)?;
+                    let mut deserializer = JsObjectAccess::new(self.cx, self.shared, val)?;
                     visitor.visit_map(&mut deserializer)
                 } else {
                     Err(serde::de::Error::invalid_type(
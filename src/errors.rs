@@ -70,6 +70,36 @@ error_chain! {
             description("CastError")
             display("CastError")
         }
+        /// occurs when deserializing a `BigInt` that does not fit
+        /// in a 128 bit integer
+        BigIntOutOfRange(value: String) {
+            description("BigInt out of range")
+            display("BigInt out of range for a 128 bit integer: {}", value)
+        }
+        /// occurs in strict integer mode when an integer is outside
+        /// of `Number.MAX_SAFE_INTEGER`
+        UnsafeInteger(value: String) {
+            description("Integer outside of Number.MAX_SAFE_INTEGER")
+            display("Integer outside of Number.MAX_SAFE_INTEGER: {}", value)
+        }
+        /// occurs when serializing an internally tagged enum variant
+        /// that does not contain a struct or a map
+        UnsupportedTaggedVariant(variant: &'static str) {
+            description("Unsupported internally tagged variant")
+            display(
+                "Internally tagged variant must contain a struct or map: {}",
+                variant
+            )
+        }
+        /// occurs when serializing a map to an object and a key is
+        /// not a string, number, bool, char or unit variant
+        InvalidMapKey(key_type: &'static str) {
+            description("Invalid map key")
+            display(
+                "Map key must be a string, number, bool, char or unit variant got: {}",
+                key_type
+            )
+        }
     }
 }
 
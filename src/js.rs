@@ -0,0 +1,371 @@
+//!
+//! Helpers for reaching JS built-ins that neon does not expose directly
+//!
+
+use errors::ErrorKind;
+use errors::Result as LibResult;
+use neon::prelude::*;
+use num;
+
+/// Looks up a function on the global object, e.g. `BigInt`
+pub fn global_function<'j, C>(cx: &mut C, name: &str) -> LibResult<Handle<'j, JsFunction>>
+where
+    C: Context<'j>,
+{
+    let global = cx.global();
+    let func = global.get(cx, name)?.downcast::<JsFunction>().or_throw(cx)?;
+    Ok(func)
+}
+
+/// Calls the global function `name` with `undefined` as `this`
+pub fn call_global<'j, C>(
+    cx: &mut C,
+    name: &str,
+    args: Vec<Handle<'j, JsValue>>,
+) -> LibResult<Handle<'j, JsValue>>
+where
+    C: Context<'j>,
+{
+    let func = global_function(cx, name)?;
+    let this = JsUndefined::new();
+    let result = func.call(cx, this, args)?;
+    Ok(result)
+}
+
+/// Converts a value to a string the way `String(value)` would
+pub fn to_js_string<'j, C>(cx: &mut C, value: Handle<'j, JsValue>) -> LibResult<String>
+where
+    C: Context<'j>,
+{
+    let string = call_global(cx, "String", vec![value])?
+        .downcast::<JsString>()
+        .or_throw(cx)?;
+    Ok(string.value())
+}
+
+/// Creates a JS `BigInt` from its decimal representation
+pub fn new_bigint<'j, C>(cx: &mut C, decimal: &str) -> LibResult<Handle<'j, JsValue>>
+where
+    C: Context<'j>,
+{
+    let decimal = JsString::new(cx, decimal).upcast();
+    call_global(cx, "BigInt", vec![decimal])
+}
+
+/// The class of a value as reported by `Object.prototype.toString`, e.g. `BigInt` or `Map`
+pub fn class_name<'j, C>(cx: &mut C, value: Handle<'j, JsValue>) -> LibResult<String>
+where
+    C: Context<'j>,
+{
+    let object = cx.global().get(cx, "Object")?.downcast::<JsObject>().or_throw(cx)?;
+    let prototype = object.get(cx, "prototype")?.downcast::<JsObject>().or_throw(cx)?;
+    let to_string = prototype.get(cx, "toString")?.downcast::<JsFunction>().or_throw(cx)?;
+    let tag = to_string
+        .call(cx, value, Vec::<Handle<JsValue>>::new())?
+        .downcast::<JsString>()
+        .or_throw(cx)?
+        .value();
+    let name = tag
+        .strip_prefix("[object ")
+        .and_then(|name| name.strip_suffix(']'))
+        .unwrap_or(&tag);
+    Ok(name.to_owned())
+}
+
+/// The typed array classes `Builtins` tells apart, in the order it holds their prototypes
+const TYPED_ARRAYS: [&str; 11] = [
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "BigInt64Array",
+    "BigUint64Array",
+];
+
+/// The `prototype` of a global constructor, e.g. `Map.prototype`
+fn global_prototype<'j, C>(cx: &mut C, name: &str) -> LibResult<Handle<'j, JsObject>>
+where
+    C: Context<'j>,
+{
+    let constructor = global_function(cx, name)?;
+    let prototype = constructor.get(cx, "prototype")?.downcast::<JsObject>().or_throw(cx)?;
+    Ok(prototype)
+}
+
+/// The built-in classes a value is tested against, looked up once and reused for every value
+///
+/// A value is tested with `Map.prototype.isPrototypeOf(value)`, what `value instanceof Map`
+/// does, so unlike its class name it can not be faked with `Symbol.toStringTag`
+#[derive(Clone, Copy)]
+pub struct Builtins<'j> {
+    is_prototype_of: Handle<'j, JsFunction>,
+    array_buffer: Handle<'j, JsFunction>,
+    is_view: Handle<'j, JsFunction>,
+    map: Handle<'j, JsObject>,
+    set: Handle<'j, JsObject>,
+    data_view: Handle<'j, JsObject>,
+    typed_arrays: [Option<Handle<'j, JsObject>>; 11],
+}
+
+impl<'j> Builtins<'j> {
+    pub fn new<C>(cx: &mut C) -> LibResult<Self>
+    where
+        C: Context<'j>,
+    {
+        let object = global_prototype(cx, "Object")?;
+        let is_prototype_of = object
+            .get(cx, "isPrototypeOf")?
+            .downcast::<JsFunction>()
+            .or_throw(cx)?;
+        let array_buffer = global_function(cx, "ArrayBuffer")?;
+        let is_view = array_buffer.get(cx, "isView")?.downcast::<JsFunction>().or_throw(cx)?;
+        let map = global_prototype(cx, "Map")?;
+        let set = global_prototype(cx, "Set")?;
+        let data_view = global_prototype(cx, "DataView")?;
+        // `BigInt64Array` and `BigUint64Array` are missing before node 10.4
+        let mut typed_arrays = [None; 11];
+        for (prototype, class) in typed_arrays.iter_mut().zip(TYPED_ARRAYS.iter()) {
+            if cx.global().get(cx, *class)?.is_a::<JsFunction>() {
+                *prototype = Some(global_prototype(cx, class)?);
+            }
+        }
+        Ok(Builtins {
+            is_prototype_of,
+            array_buffer,
+            is_view,
+            map,
+            set,
+            data_view,
+            typed_arrays,
+        })
+    }
+
+    fn is_instance<C>(
+        &self,
+        cx: &mut C,
+        value: Handle<'j, JsValue>,
+        prototype: Handle<'j, JsObject>,
+    ) -> LibResult<bool>
+    where
+        C: Context<'j>,
+    {
+        let result = self
+            .is_prototype_of
+            .call(cx, prototype, vec![value])?
+            .downcast::<JsBoolean>()
+            .or_throw(cx)?;
+        Ok(result.value())
+    }
+
+    /// The class of `value` when it is a `Map`, `Set`, `DataView` or typed array,
+    /// a node `Buffer` is a `Uint8Array`
+    pub fn class_of<C>(
+        &self,
+        cx: &mut C,
+        value: Handle<'j, JsValue>,
+    ) -> LibResult<Option<&'static str>>
+    where
+        C: Context<'j>,
+    {
+        if self.is_instance(cx, value, self.map)? {
+            return Ok(Some("Map"));
+        }
+        if self.is_instance(cx, value, self.set)? {
+            return Ok(Some("Set"));
+        }
+        let is_view = self
+            .is_view
+            .call(cx, self.array_buffer, vec![value])?
+            .downcast::<JsBoolean>()
+            .or_throw(cx)?;
+        if !is_view.value() {
+            return Ok(None);
+        }
+        if self.is_instance(cx, value, self.data_view)? {
+            return Ok(Some("DataView"));
+        }
+        for (prototype, class) in self.typed_arrays.iter().zip(TYPED_ARRAYS.iter()) {
+            if let Some(prototype) = *prototype {
+                if self.is_instance(cx, value, prototype)? {
+                    return Ok(Some(*class));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Calls `new name(...args)` where `name` is a global constructor, e.g. `Map`
+pub fn construct_global<'j, C>(
+    cx: &mut C,
+    name: &str,
+    args: Vec<Handle<'j, JsValue>>,
+) -> LibResult<Handle<'j, JsObject>>
+where
+    C: Context<'j>,
+{
+    let constructor = global_function(cx, name)?;
+    let instance = constructor.construct(cx, args)?;
+    Ok(instance)
+}
+
+/// Calls the method `name` of `this`, e.g. `map.set(key, value)`
+pub fn call_method<'j, C>(
+    cx: &mut C,
+    this: Handle<'j, JsObject>,
+    name: &str,
+    args: Vec<Handle<'j, JsValue>>,
+) -> LibResult<Handle<'j, JsValue>>
+where
+    C: Context<'j>,
+{
+    let method = this.get(cx, name)?.downcast::<JsFunction>().or_throw(cx)?;
+    let result = method.call(cx, this, args)?;
+    Ok(result)
+}
+
+/// Collects an iterable into an array with `Array.from`, e.g. the entries of a `Map`
+pub fn array_from<'j, C>(
+    cx: &mut C,
+    iterable: Handle<'j, JsValue>,
+) -> LibResult<Handle<'j, JsArray>>
+where
+    C: Context<'j>,
+{
+    let array = cx.global().get(cx, "Array")?.downcast::<JsObject>().or_throw(cx)?;
+    let result = call_method(cx, array, "from", vec![iterable])?
+        .downcast::<JsArray>()
+        .or_throw(cx)?;
+    Ok(result)
+}
+
+/// Creates an object without a prototype with `Object.create(null)`
+pub fn new_null_prototype_object<'j, C>(cx: &mut C) -> LibResult<Handle<'j, JsObject>>
+where
+    C: Context<'j>,
+{
+    let object = cx.global().get(cx, "Object")?.downcast::<JsObject>().or_throw(cx)?;
+    let null = JsNull::new().upcast();
+    let created = call_method(cx, object, "create", vec![null])?
+        .downcast::<JsObject>()
+        .or_throw(cx)?;
+    Ok(created)
+}
+
+/// Creates an own data property with `Object.defineProperty`, unlike `object.set`
+/// a key of `__proto__` creates a property instead of replacing the prototype
+pub fn define_own_property<'j, C>(
+    cx: &mut C,
+    object: Handle<'j, JsObject>,
+    key: Handle<'j, JsValue>,
+    value: Handle<'j, JsValue>,
+) -> LibResult<()>
+where
+    C: Context<'j>,
+{
+    let descriptor = JsObject::new(cx);
+    descriptor.set(cx, "value", value)?;
+    for flag in &["writable", "enumerable", "configurable"] {
+        let t = JsBoolean::new(cx, true);
+        descriptor.set(cx, *flag, t)?;
+    }
+    let global_object = cx.global().get(cx, "Object")?.downcast::<JsObject>().or_throw(cx)?;
+    call_method(
+        cx,
+        global_object,
+        "defineProperty",
+        vec![object.upcast(), key, descriptor.upcast()],
+    )?;
+    Ok(())
+}
+
+/// True when the prototype of `object` is `Object.prototype` or `null`,
+/// unlike its class name this can not be changed with `Symbol.toStringTag`
+pub fn is_plain_object<'j, C>(cx: &mut C, object: Handle<'j, JsObject>) -> LibResult<bool>
+where
+    C: Context<'j>,
+{
+    let global_object = cx.global().get(cx, "Object")?.downcast::<JsObject>().or_throw(cx)?;
+    let prototype = call_method(cx, global_object, "getPrototypeOf", vec![object.upcast()])?;
+    if prototype.is_a::<JsNull>() {
+        return Ok(true);
+    }
+    let object_prototype = global_object.get(cx, "prototype")?;
+    let same = call_method(cx, global_object, "is", vec![prototype, object_prototype])?
+        .downcast::<JsBoolean>()
+        .or_throw(cx)?;
+    Ok(same.value())
+}
+
+/// Copies an object with its prototype and the descriptors of its own properties,
+/// unlike `Object.assign` a `__proto__` property is copied instead of changing the prototype
+pub fn copy_object<'j, C>(
+    cx: &mut C,
+    source: Handle<'j, JsObject>,
+) -> LibResult<Handle<'j, JsObject>>
+where
+    C: Context<'j>,
+{
+    let global_object = cx.global().get(cx, "Object")?.downcast::<JsObject>().or_throw(cx)?;
+    let prototype = call_method(cx, global_object, "getPrototypeOf", vec![source.upcast()])?;
+    let descriptors =
+        call_method(cx, global_object, "getOwnPropertyDescriptors", vec![source.upcast()])?;
+    let copy = call_method(cx, global_object, "create", vec![prototype, descriptors])?
+        .downcast::<JsObject>()
+        .or_throw(cx)?;
+    Ok(copy)
+}
+
+/// Reads a numeric property that holds a size or offset, e.g. `byteLength`
+fn usize_property<'j, C>(cx: &mut C, object: Handle<'j, JsObject>, name: &str) -> LibResult<usize>
+where
+    C: Context<'j>,
+{
+    let value = object.get(cx, name)?.downcast::<JsNumber>().or_throw(cx)?.value();
+    match num::cast::<f64, usize>(value) {
+        Some(n) => Ok(n),
+        None => bail!(ErrorKind::CastError),
+    }
+}
+
+/// Copies the bytes seen by an `ArrayBuffer` view, e.g. a `Float64Array` or a `DataView`
+pub fn copy_view_bytes<'j, C>(cx: &mut C, view: Handle<'j, JsObject>) -> LibResult<Vec<u8>>
+where
+    C: Context<'j>,
+{
+    let buffer = view.get(cx, "buffer")?.downcast::<JsArrayBuffer>().or_throw(cx)?;
+    let offset = usize_property(cx, view, "byteOffset")?;
+    let len = usize_property(cx, view, "byteLength")?;
+    let copy = cx.borrow(&buffer, |buffer| {
+        let bytes: &[u8] = buffer.as_slice();
+        Vec::from(&bytes[offset..offset + len])
+    });
+    Ok(copy)
+}
+
+/// Replaces an object by the result of its `toJSON` method when it has one,
+/// `Buffer`s are left alone so they can still be read as bytes
+pub fn call_to_json<'j, C>(cx: &mut C, value: Handle<'j, JsValue>) -> LibResult<Handle<'j, JsValue>>
+where
+    C: Context<'j>,
+{
+    if value.is_a::<JsBuffer>() {
+        return Ok(value);
+    }
+    let object = match value.downcast::<JsObject>() {
+        Ok(object) => object,
+        Err(_) => return Ok(value),
+    };
+    match object.get(cx, "toJSON")?.downcast::<JsFunction>() {
+        Ok(to_json) => {
+            let result = to_json.call(cx, object, Vec::<Handle<JsValue>>::new())?;
+            Ok(result)
+        }
+        Err(_) => Ok(value),
+    }
+}
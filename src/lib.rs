@@ -21,10 +21,37 @@
 //! Convert a `Handle<js::JsValue>` to
 //! a type implementing `serde::Deserialize`
 //!
+//! #### `neon_serde::from_value_with`
+//! Same as `from_value` but takes a `DeserializerOptions`,
+//! so each function can choose between lenient and strict parsing
+//!
 //! #### `neon_serde::to_value`
 //! Convert a value implementing `serde::Serialize` to
 //! a `Handle<JsValue>`
 //!
+//! #### `neon_serde::to_value_with`
+//! Same as `to_value` but takes a `SerializerOptions`,
+//! e.g. to produce a `BigInt` for large `u64` values.
+//! The options apply to every nested value, including enum variants
+//!
+//! ```rust,no_run
+//! # extern crate neon_serde;
+//! # extern crate neon;
+//! # use neon::prelude::*;
+//! use neon_serde::ser::BigIntMode;
+//! use neon_serde::SerializerOptions;
+//!
+//! fn serialize_ids(mut cx: FunctionContext) -> JsResult<JsValue> {
+//!     let opts = SerializerOptions::new().bigint(BigIntMode::WhenUnsafe);
+//!     let ids: Vec<u64> = vec![1, 1 << 60];
+//!
+//!     let js_value = neon_serde::to_value_with(&mut cx, &ids, &opts)?;
+//!     Ok(js_value)
+//! }
+//! # fn main () {
+//! # }
+//! ```
+//!
 //!
 //! ## Example
 //!
@@ -79,13 +106,30 @@ extern crate serde;
 
 pub mod ser;
 pub mod de;
+pub mod callback;
+pub mod date;
 pub mod errors;
+pub mod patch;
+pub mod raw;
+pub mod set;
+pub mod typed_array;
 
+mod js;
 mod macros;
 
+pub use callback::Callback;
+pub use date::Date;
 pub use de::from_value;
 pub use de::from_value_opt;
+pub use de::from_value_opt_with;
+pub use de::from_value_with;
+pub use de::DeserializerOptions;
+pub use patch::Patch;
+pub use raw::RawValue;
 pub use ser::to_value;
+pub use ser::to_value_with;
+pub use ser::SerializerOptions;
+pub use typed_array::TypedArray;
 
 #[cfg(test)]
 mod tests {
@@ -122,4 +166,21 @@ mod tests {
         let _ = check;
     }
 
+    #[test]
+    fn test_it_compiles_with_options() {
+        fn check<'j>(mut cx: FunctionContext<'j>) -> JsResult<'j, JsValue> {
+            let de_opts = DeserializerOptions::new().strict_integers(true);
+            let ser_opts = SerializerOptions::new().strict_integers(true);
+            let result: () = {
+                let arg: Option<Handle<'j, JsValue>> = cx.argument_opt(0);
+                let () = from_value_opt_with(&mut cx, arg, &de_opts)?;
+                ()
+            };
+            let result: Handle<'j, JsValue> = to_value_with(&mut cx, &result, &ser_opts)?;
+            Ok(result)
+        }
+
+        let _ = check;
+    }
+
 }
@@ -0,0 +1,134 @@
+//!
+//! A value that can be missing, `null` or set
+//!
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Name used to recognize a `Patch` in `deserialize_newtype_struct`
+/// and a missing one in `serialize_newtype_struct`
+pub(crate) const TOKEN: &str = "$neon_serde::private::Patch";
+
+/// A field of a PATCH style update that tells apart a property that is
+/// `undefined` or missing from one that is explicitly `null`
+///
+/// Use `#[serde(default)]` on the field so a missing property is `Patch::Missing`.
+/// Other deserializers can not tell the two apart and produce `Patch::Null` for `null`.
+///
+/// ```rust,no_run
+/// # #![allow(dead_code)]
+/// extern crate neon_serde;
+/// #[macro_use]
+/// extern crate serde_derive;
+///
+/// use neon_serde::Patch;
+///
+/// #[derive(Deserialize)]
+/// struct UserUpdate {
+///     #[serde(default)]
+///     nickname: Patch<String>,
+/// }
+/// # fn main () {
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Patch<T> {
+    /// the property is missing or `undefined`
+    Missing,
+    /// the property is `null`
+    Null,
+    /// the property holds a value
+    Value(T),
+}
+
+impl<T> Default for Patch<T> {
+    fn default() -> Self {
+        Patch::Missing
+    }
+}
+
+impl<T> Patch<T> {
+    /// True for `Patch::Missing`,
+    /// e.g. `#[serde(skip_serializing_if = "Patch::is_missing")]`
+    pub fn is_missing(&self) -> bool {
+        match *self {
+            Patch::Missing => true,
+            _ => false,
+        }
+    }
+
+    /// Converts to `None` when missing, `Some(None)` when `null`
+    pub fn into_option(self) -> Option<Option<T>> {
+        match self {
+            Patch::Missing => None,
+            Patch::Null => Some(None),
+            Patch::Value(v) => Some(Some(v)),
+        }
+    }
+}
+
+/// `Missing` is serialized as `undefined` and left out of structs, `Null` as `null`,
+/// other serializers see `Missing` as a unit
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            Patch::Missing => serializer.serialize_newtype_struct(TOKEN, &()),
+            Patch::Null => serializer.serialize_unit(),
+            Patch::Value(ref v) => serializer.serialize_some(v),
+        }
+    }
+}
+
+struct PatchVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for PatchVisitor<T> {
+    type Value = Patch<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("undefined, null or a value")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Patch::Missing)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Patch::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Patch::Value)
+    }
+
+    /// other deserializers only know about `null`
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Option::<T>::deserialize(deserializer)?;
+        Ok(value.map_or(Patch::Null, Patch::Value))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, PatchVisitor(PhantomData))
+    }
+}
@@ -0,0 +1,161 @@
+//!
+//! Carry a `JsValue` through a Rust data structure without converting it
+//!
+//! `RawValue` holds the original `Handle` when deserialized by `from_value`
+//! and gives back that same handle when serialized by `to_value`. It works the
+//! way serde_json's `RawValue` does, the handle is passed around the visitor
+//! next to a magic name the `Serializer` and `Deserializer` recognize.
+//!
+//! ```rust,no_run
+//! # #![allow(dead_code)]
+//! extern crate neon_serde;
+//! #[macro_use]
+//! extern crate serde_derive;
+//!
+//! use neon_serde::RawValue;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Job<'j> {
+//!     name: String,
+//!     // handed back to JS untouched
+//!     #[serde(borrow)]
+//!     payload: RawValue<'j>,
+//! }
+//! # fn main () {
+//! # }
+//! ```
+//!
+//! Other serializers and deserializers can not convert a `RawValue`. Its lifetime
+//! is the one of the handle it was deserialized from, so it can not outlive its context:
+//!
+//! ```rust,compile_fail
+//! # extern crate neon_serde;
+//! # extern crate neon;
+//! # use neon::prelude::*;
+//! use neon_serde::RawValue;
+//!
+//! fn keep(mut cx: FunctionContext) -> JsResult<JsValue> {
+//!     let arg0 = cx.argument::<JsValue>(0)?;
+//!     let raw: RawValue<'static> = neon_serde::from_value(&mut cx, arg0)?;
+//!     Ok(raw.handle())
+//! }
+//! # fn main () {
+//! # }
+//! ```
+//!
+
+use neon::prelude::*;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::{ser, Serialize, Serializer};
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+
+/// Name used to recognize a raw value in `serialize_newtype_struct`
+/// and `deserialize_newtype_struct`
+pub(crate) const TOKEN: &str = "$neon_serde::private::RawValue";
+
+/// Message of the error returned when a `RawValue` meets another serializer or deserializer
+const OUTSIDE_NEON: &str = "RawValue can only be converted by neon_serde";
+
+thread_local! {
+    /// The handle being passed between a `RawValue` and the `Serializer` or `Deserializer`,
+    /// serde has no way to pass a value that is not owned data through a visitor
+    static SLOT: Cell<Option<Handle<'static, JsValue>>> = Cell::new(None);
+}
+
+/// Stores the handle for the `RawValue` being converted, returns the previous one
+pub(crate) fn put<'j>(handle: Option<Handle<'j, JsValue>>) -> Option<Handle<'j, JsValue>> {
+    // the slot is only filled while a `Deserializer<'j>` visits a `Visitor<'j>`, or
+    // while a `RawValue<'j>` is serialized, so the handle is taken back with its own lifetime
+    // or a shorter one
+    let handle: Option<Handle<'static, JsValue>> = unsafe { mem::transmute(handle) };
+    let previous = SLOT.with(|slot| slot.replace(handle));
+    unsafe { mem::transmute(previous) }
+}
+
+/// Takes the handle stored by `put`
+pub(crate) fn take<'j>() -> Option<Handle<'j, JsValue>> {
+    put(None)
+}
+
+/// A `JsValue` that is passed through unchanged, see the module documentation
+#[derive(Clone, Copy)]
+pub struct RawValue<'j>(pub Handle<'j, JsValue>);
+
+impl<'j> RawValue<'j> {
+    /// The wrapped handle
+    pub fn handle(&self) -> Handle<'j, JsValue> {
+        self.0
+    }
+}
+
+impl<'j, T: Value> From<Handle<'j, T>> for RawValue<'j> {
+    fn from(handle: Handle<'j, T>) -> Self {
+        RawValue(handle.upcast())
+    }
+}
+
+impl<'j> fmt::Debug for RawValue<'j> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("RawValue")
+    }
+}
+
+impl<'j> Serialize for RawValue<'j> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let previous = put(Some(self.0));
+        let result = serializer.serialize_newtype_struct(TOKEN, &OutsideNeon);
+        put(previous);
+        result
+    }
+}
+
+/// What other serializers see instead of the handle
+struct OutsideNeon;
+
+impl Serialize for OutsideNeon {
+    fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Err(ser::Error::custom(OUTSIDE_NEON))
+    }
+}
+
+struct RawValueVisitor<'j>(PhantomData<Handle<'j, JsValue>>);
+
+/// `'de` is the lifetime of the `Deserializer` input, the handle lives at least as long
+impl<'de: 'j, 'j> Visitor<'de> for RawValueVisitor<'j> {
+    type Value = RawValue<'j>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a JS value")
+    }
+
+    /// the `Deserializer` stores the handle before visiting unit
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match take::<'de>() {
+            Some(handle) => Ok(RawValue(handle)),
+            None => Err(E::custom(OUTSIDE_NEON)),
+        }
+    }
+}
+
+/// Borrows from the `Deserializer` input like `&'j str` does, a `RawValue<'static>`
+/// is not `DeserializeOwned`
+impl<'de: 'j, 'j> Deserialize<'de> for RawValue<'j> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueVisitor(PhantomData))
+    }
+}
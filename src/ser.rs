@@ -2,13 +2,22 @@
 //! Serialize a Rust data structure into a `JsValue`
 //!
 
+use date;
 use errors::Error;
 use errors::ErrorKind;
 use errors::Result as LibResult;
+use js;
 use neon::prelude::*;
+use num;
+use patch;
+use raw;
 use serde::ser::{self, Serialize};
+use set;
 use std::marker::PhantomData;
-use num;
+use typed_array;
+
+/// The largest integer a JS `Number` can represent exactly, `Number.MAX_SAFE_INTEGER`
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
 
 fn as_num<T: num::cast::NumCast, OutT: num::cast::NumCast>(n: T) -> LibResult<OutT> {
     match num::cast::<T, OutT>(n) {
@@ -17,6 +26,182 @@ fn as_num<T: num::cast::NumCast, OutT: num::cast::NumCast>(n: T) -> LibResult<Ou
     }
 }
 
+fn is_safe_integer<T: num::cast::NumCast>(n: T) -> bool {
+    match num::cast::<T, i64>(n) {
+        Some(n) => -MAX_SAFE_INTEGER <= n && n <= MAX_SAFE_INTEGER,
+        None => false,
+    }
+}
+
+/// Controls when 64 and 128 bit integers are serialized as a JS `BigInt`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BigIntMode {
+    /// Always produce a `Number`, values above `Number.MAX_SAFE_INTEGER` lose precision
+    Never,
+    /// Produce a `BigInt` only for values outside of `Number.MAX_SAFE_INTEGER`
+    WhenUnsafe,
+    /// Always produce a `BigInt`
+    Always,
+}
+
+impl Default for BigIntMode {
+    fn default() -> Self {
+        BigIntMode::Never
+    }
+}
+
+/// Controls what kind of JS value a Rust map is serialized to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapMode {
+    /// A plain object, keys are converted to property names
+    Object,
+    /// An ES `Map`, keys keep their JS type
+    Map,
+    /// An array of `[key, value]` entries, works for any key type
+    /// and can be passed to `new Map(entries)`
+    Entries,
+}
+
+impl Default for MapMode {
+    fn default() -> Self {
+        MapMode::Object
+    }
+}
+
+/// Controls what kind of JS value bytes are serialized to,
+/// e.g. a `serde_bytes::ByteBuf`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BytesMode {
+    /// A node `Buffer`
+    Buffer,
+    /// A `Uint8Array` that is not a node `Buffer`
+    Uint8Array,
+    /// An `ArrayBuffer`, e.g. to use in a transfer list
+    ArrayBuffer,
+}
+
+impl Default for BytesMode {
+    fn default() -> Self {
+        BytesMode::Buffer
+    }
+}
+
+/// Controls how enum variants are represented, the names match serde's
+/// [enum representations](https://serde.rs/enum-representations.html)
+/// but apply to every enum without attributes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnumTagging {
+    /// `{"Variant": content}`, unit variants are `"Variant"`
+    External,
+    /// `{tag: "Variant", ...fields}`, only for variants holding a struct or map
+    Internal { tag: String },
+    /// `{tag: "Variant", content: content}`
+    Adjacent { tag: String, content: String },
+    /// `content` without the variant name
+    Untagged,
+}
+
+impl Default for EnumTagging {
+    fn default() -> Self {
+        EnumTagging::External
+    }
+}
+
+/// Options changing how values are converted by `to_value_with`
+#[derive(Clone, Debug, Default)]
+pub struct SerializerOptions {
+    bigint: BigIntMode,
+    strict_integers: bool,
+    maps: MapMode,
+    null_prototype_maps: bool,
+    null_prototype_structs: bool,
+    bytes: BytesMode,
+    enums: EnumTagging,
+    unit_variant_index: bool,
+    omit_none: bool,
+}
+
+impl SerializerOptions {
+    /// Options matching the behaviour of `to_value`
+    pub fn new() -> Self {
+        SerializerOptions::default()
+    }
+
+    /// Sets how `i64` `u64` `i128` and `u128` are serialized
+    pub fn bigint(mut self, mode: BigIntMode) -> Self {
+        self.bigint = mode;
+        self
+    }
+
+    /// When set an integer outside of `Number.MAX_SAFE_INTEGER` is an `UnsafeInteger`
+    /// error, the same rule `DeserializerOptions::strict_integers` uses. Integers
+    /// serialized as a `BigInt` are never rounded
+    pub fn strict_integers(mut self, strict: bool) -> Self {
+        self.strict_integers = strict;
+        self
+    }
+
+    /// Sets what `HashMap`, `BTreeMap` and other maps are serialized to
+    pub fn maps(mut self, mode: MapMode) -> Self {
+        self.maps = mode;
+        self
+    }
+
+    /// When set maps serialized to objects are created with `Object.create(null)`
+    /// so keys like `toString` do not collide with inherited properties
+    ///
+    /// a `__proto__` key is always an own property, with or without this option
+    pub fn null_prototype_maps(mut self, null_prototype: bool) -> Self {
+        self.null_prototype_maps = null_prototype;
+        self
+    }
+
+    /// When set structs are created with `Object.create(null)`
+    pub fn null_prototype_structs(mut self, null_prototype: bool) -> Self {
+        self.null_prototype_structs = null_prototype;
+        self
+    }
+
+    /// Sets what bytes are serialized to, the default is a node `Buffer`
+    pub fn bytes(mut self, mode: BytesMode) -> Self {
+        self.bytes = mode;
+        self
+    }
+
+    /// Sets how enum variants are represented, e.g. as discriminated unions
+    /// with `EnumTagging::Internal { tag: "type".into() }`
+    pub fn enums(mut self, tagging: EnumTagging) -> Self {
+        self.enums = tagging;
+        self
+    }
+
+    /// When set unit variants are serialized as their index instead of their name,
+    /// e.g. to match a TypeScript numeric `enum`. Applies with any `EnumTagging`
+    pub fn unit_variant_index(mut self, as_index: bool) -> Self {
+        self.unit_variant_index = as_index;
+        self
+    }
+
+    /// When set `None` is serialized as `undefined` instead of `null`,
+    /// struct fields and object map entries holding `None` are left out
+    /// the same way `JSON.stringify` leaves out `undefined` properties
+    pub fn omit_none(mut self, omit: bool) -> Self {
+        self.omit_none = omit;
+        self
+    }
+
+    fn new_object<'j, C>(&self, cx: &mut C, null_prototype: bool) -> LibResult<Handle<'j, JsObject>>
+    where
+        C: Context<'j>,
+    {
+        if null_prototype {
+            js::new_null_prototype_object(cx)
+        } else {
+            Ok(JsObject::new(cx))
+        }
+    }
+}
+
 /// Converts a value of type `V` to a `JsValue`
 ///
 /// # Errors
@@ -30,10 +215,25 @@ where
     C: Context<'j>,
     V: Serialize + ?Sized,
 {
-    let serializer = Serializer {
-        cx,
-        ph: PhantomData,
-    };
+    to_value_with(cx, value, &SerializerOptions::default())
+}
+
+/// Converts a value of type `V` to a `JsValue` using the given options
+///
+/// # Errors
+///
+/// See `to_value`
+///
+pub fn to_value_with<'j, C, V>(
+    cx: &mut C,
+    value: &V,
+    opts: &SerializerOptions,
+) -> LibResult<Handle<'j, JsValue>>
+where
+    C: Context<'j>,
+    V: Serialize + ?Sized,
+{
+    let serializer = Serializer::new(cx, opts);
     let serialized_value = value.serialize(serializer)?;
     Ok(serialized_value)
 }
@@ -44,6 +244,9 @@ where
     C: Context<'j>,
 {
     cx: &'a mut C,
+    opts: &'a SerializerOptions,
+    /// set when the value is a handle the caller still holds, e.g. from a `RawValue`
+    shared: Option<&'a mut bool>,
     ph: PhantomData<&'j ()>,
 }
 
@@ -53,6 +256,7 @@ where
     C: Context<'j>,
 {
     cx: &'a mut C,
+    opts: &'a SerializerOptions,
     array: Handle<'j, JsArray>,
 }
 
@@ -61,7 +265,7 @@ pub struct TupleVariantSerializer<'a, 'j, C: 'a>
 where
     C: Context<'j>,
 {
-    outter_object: Handle<'j, JsObject>,
+    variant: &'static str,
     inner: ArraySerializer<'a, 'j, C>,
 }
 
@@ -71,8 +275,22 @@ where
     C: Context<'j>,
 {
     cx: &'a mut C,
+    opts: &'a SerializerOptions,
     object: Handle<'j, JsObject>,
     key_holder: Handle<'j, JsObject>,
+    key_is_proto: bool,
+    len: u32,
+}
+
+#[doc(hidden)]
+pub struct MapKeySerializer<'a, 'j, C: 'a>
+where
+    C: Context<'j>,
+{
+    cx: &'a mut C,
+    /// set when the key is `__proto__`, which `object.set` would treat as the prototype
+    is_proto: &'a mut bool,
+    ph: PhantomData<&'j ()>,
 }
 
 #[doc(hidden)]
@@ -81,6 +299,7 @@ where
     C: Context<'j>,
 {
     cx: &'a mut C,
+    opts: &'a SerializerOptions,
     object: Handle<'j, JsObject>,
 }
 
@@ -89,10 +308,60 @@ pub struct StructVariantSerializer<'a, 'j, C: 'a>
 where
     C: Context<'j>,
 {
-    outer_object: Handle<'j, JsObject>,
+    variant: &'static str,
     inner: StructSerializer<'a, 'j, C>,
 }
 
+/// Wraps the content of an enum variant as chosen by `SerializerOptions::enums`
+///
+/// `shared` content is an object the caller still holds, e.g. from a `RawValue`,
+/// an internal tag is then added to a copy of it
+fn tag_variant<'j, C>(
+    cx: &mut C,
+    opts: &SerializerOptions,
+    variant: &'static str,
+    content: Handle<'j, JsValue>,
+    shared: bool,
+) -> LibResult<Handle<'j, JsValue>>
+where
+    C: Context<'j>,
+{
+    match opts.enums {
+        EnumTagging::External => {
+            let object = JsObject::new(cx);
+            object.set(cx, variant, content)?;
+            Ok(object.upcast())
+        }
+        EnumTagging::Internal { ref tag } => {
+            // only plain objects, a `Map`, `Date` or typed array would hide the tag
+            let object = match content.downcast::<JsObject>() {
+                _ if content.is_a::<JsNull>() || content.is_a::<JsUndefined>() => JsObject::new(cx),
+                Ok(object) if js::is_plain_object(cx, object)? => object,
+                _ => bail!(ErrorKind::UnsupportedTaggedVariant(variant)),
+            };
+            let object = if shared {
+                js::copy_object(cx, object)?
+            } else {
+                object
+            };
+            let variant = JsString::new(cx, variant);
+            object.set(cx, tag.as_str(), variant)?;
+            Ok(object.upcast())
+        }
+        EnumTagging::Adjacent {
+            ref tag,
+            content: ref content_key,
+        } => {
+            let object = JsObject::new(cx);
+            let variant = JsString::new(cx, variant);
+            object.set(cx, tag.as_str(), variant)?;
+            object.set(cx, content_key.as_str(), content)?;
+            Ok(object.upcast())
+        }
+        EnumTagging::Untagged => Ok(content),
+    }
+}
+
 #[doc(hidden)]
 impl<'a, 'j, C> ser::Serializer for Serializer<'a, 'j, C>
 where
@@ -131,12 +400,12 @@ where
 
     #[inline]
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        Ok(JsNumber::new(self.cx, as_num::<_, f64>(v)?).upcast())
+        self.serialize_wide_integer(v)
     }
 
     #[inline]
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-        Ok(JsNumber::new(self.cx, as_num::<_, f64>(v)?).upcast())
+        self.serialize_wide_integer(v)
     }
 
 
@@ -157,12 +426,12 @@ where
 
     #[inline]
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        Ok(JsNumber::new(self.cx, as_num::<_, f64>(v)?).upcast())
+        self.serialize_wide_integer(v)
     }
 
     #[inline]
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-        Ok(JsNumber::new(self.cx, as_num::<_, f64>(v)?).upcast())
+        self.serialize_wide_integer(v)
     }
 
     #[inline]
@@ -192,13 +461,27 @@ where
 
     #[inline]
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        let mut buff = JsBuffer::new(self.cx, as_num::<_, u32>(v.len())?)?;
-        self.cx.borrow_mut(&mut buff, |buff| buff.as_mut_slice().clone_from_slice(v));
-        Ok(buff.upcast())
+        let len = as_num::<_, u32>(v.len())?;
+        if self.opts.bytes == BytesMode::Buffer {
+            let mut buff = JsBuffer::new(self.cx, len)?;
+            self.cx.borrow_mut(&mut buff, |buff| buff.as_mut_slice().clone_from_slice(v));
+            return Ok(buff.upcast());
+        }
+
+        let mut array_buffer = JsArrayBuffer::new(self.cx, len)?;
+        self.cx.borrow_mut(&mut array_buffer, |buff| buff.as_mut_slice().clone_from_slice(v));
+        if self.opts.bytes == BytesMode::Uint8Array {
+            let view = js::construct_global(self.cx, "Uint8Array", vec![array_buffer.upcast()])?;
+            return Ok(view.upcast());
+        }
+        Ok(array_buffer.upcast())
     }
 
     #[inline]
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        if self.opts.omit_none {
+            return Ok(JsUndefined::new().upcast());
+        }
         Ok(JsNull::new().upcast())
     }
 
@@ -224,21 +507,63 @@ where
     fn serialize_unit_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        self.serialize_str(variant)
+        if self.opts.unit_variant_index {
+            return Ok(JsNumber::new(self.cx, variant_index).upcast());
+        }
+        match self.opts.enums {
+            EnumTagging::External => self.serialize_str(variant),
+            EnumTagging::Internal { ref tag } | EnumTagging::Adjacent { ref tag, .. } => {
+                let object = JsObject::new(self.cx);
+                let variant = JsString::new(self.cx, variant);
+                object.set(self.cx, tag.as_str(), variant)?;
+                Ok(object.upcast())
+            }
+            EnumTagging::Untagged => Ok(JsNull::new().upcast()),
+        }
     }
 
     #[inline]
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
+        if let Some(class) = typed_array::class_for_token(name) {
+            let bytes_opts = SerializerOptions {
+                bytes: BytesMode::ArrayBuffer,
+                ..self.opts.clone()
+            };
+            let buffer = value.serialize(Serializer::new(self.cx, &bytes_opts))?;
+            let view = js::construct_global(self.cx, class, vec![buffer])?;
+            return Ok(view.upcast());
+        }
+        if name == set::TOKEN {
+            let values = value.serialize(Serializer::new(self.cx, self.opts))?;
+            let js_set = js::construct_global(self.cx, "Set", vec![values])?;
+            return Ok(js_set.upcast());
+        }
+        if name == date::TOKEN {
+            let millis = value.serialize(Serializer::new(self.cx, self.opts))?;
+            let js_date = js::construct_global(self.cx, "Date", vec![millis])?;
+            return Ok(js_date.upcast());
+        }
+        if name == patch::TOKEN {
+            return Ok(JsUndefined::new().upcast());
+        }
+        if name == raw::TOKEN {
+            if let Some(handle) = raw::take() {
+                if let Some(shared) = self.shared {
+                    *shared = true;
+                }
+                return Ok(handle);
+            }
+        }
         value.serialize(self)
     }
 
@@ -253,21 +578,19 @@ where
     where
         T: Serialize,
     {
-        let obj = JsObject::new(&mut *self.cx);
-        let value_js = to_value(self.cx, value)?;
-        obj.set(self.cx, variant, value_js)?;
-
-        Ok(obj.upcast())
+        let mut shared = false;
+        let value_js = value.serialize(Serializer::with_shared(self.cx, self.opts, &mut shared))?;
+        tag_variant(self.cx, self.opts, variant, value_js, shared)
     }
 
     #[inline]
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        Ok(ArraySerializer::new(self.cx))
+        Ok(ArraySerializer::new(self.cx, self.opts))
     }
 
     #[inline]
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        Ok(ArraySerializer::new(self.cx))
+        Ok(ArraySerializer::new(self.cx, self.opts))
     }
 
     #[inline]
@@ -276,7 +599,7 @@ where
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        Ok(ArraySerializer::new(self.cx))
+        Ok(ArraySerializer::new(self.cx, self.opts))
     }
 
     #[inline]
@@ -287,12 +610,12 @@ where
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        TupleVariantSerializer::new(self.cx, variant)
+        TupleVariantSerializer::new(self.cx, self.opts, variant)
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        Ok(MapSerializer::new(self.cx))
+        MapSerializer::new(self.cx, self.opts)
     }
 
     #[inline]
@@ -301,7 +624,7 @@ where
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(StructSerializer::new(self.cx))
+        StructSerializer::new(self.cx, self.opts)
     }
 
     #[inline]
@@ -312,7 +635,54 @@ where
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        StructVariantSerializer::new(self.cx, variant)
+        StructVariantSerializer::new(self.cx, self.opts, variant)
+    }
+}
+
+#[doc(hidden)]
+impl<'a, 'j, C> Serializer<'a, 'j, C>
+where
+    C: Context<'j>,
+{
+    /// Every nested value must be serialized through a serializer created
+    /// with the parent's options so they apply at any depth
+    #[inline]
+    fn new(cx: &'a mut C, opts: &'a SerializerOptions) -> Self {
+        Serializer {
+            cx,
+            opts,
+            shared: None,
+            ph: PhantomData,
+        }
+    }
+
+    /// Sets `shared` when the serialized value is a handle the caller still holds
+    #[inline]
+    fn with_shared(cx: &'a mut C, opts: &'a SerializerOptions, shared: &'a mut bool) -> Self {
+        Serializer {
+            cx,
+            opts,
+            shared: Some(shared),
+            ph: PhantomData,
+        }
+    }
+
+    fn serialize_wide_integer<T>(self, v: T) -> LibResult<Handle<'j, JsValue>>
+    where
+        T: num::cast::NumCast + ToString + Copy,
+    {
+        let as_bigint = match self.opts.bigint {
+            BigIntMode::Never => false,
+            BigIntMode::WhenUnsafe => !is_safe_integer(v),
+            BigIntMode::Always => true,
+        };
+        if as_bigint {
+            return js::new_bigint(self.cx, &v.to_string());
+        }
+        if self.opts.strict_integers && !is_safe_integer(v) {
+            bail!(ErrorKind::UnsafeInteger(v.to_string()))
+        }
+        Ok(JsNumber::new(self.cx, as_num::<_, f64>(v)?).upcast())
     }
 }
 
@@ -322,9 +692,9 @@ where
     C: Context<'j>,
 {
     #[inline]
-    fn new(cx: &'a mut C) -> Self {
+    fn new(cx: &'a mut C, opts: &'a SerializerOptions) -> Self {
         let array = JsArray::new(cx, 0);
-        ArraySerializer { cx, array }
+        ArraySerializer { cx, opts, array }
     }
 }
 
@@ -340,7 +710,7 @@ where
     where
         T: Serialize,
     {
-        let value = to_value(self.cx, value)?;
+        let value = value.serialize(Serializer::new(self.cx, self.opts))?;
 
         let arr: Handle<'j, JsArray> = self.array;
         let len = arr.len();
@@ -402,16 +772,13 @@ impl<'a, 'j, C> TupleVariantSerializer<'a, 'j, C>
 where
     C: Context<'j>,
 {
-    fn new(cx: &'a mut C, key: &'static str) -> LibResult<Self> {
-        let inner_array = JsArray::new(cx, 0);
-        let outter_object = JsObject::new(cx);
-        outter_object.set(cx, key, inner_array)?;
+    fn new(cx: &'a mut C, opts: &'a SerializerOptions, variant: &'static str) -> LibResult<Self> {
+        if let EnumTagging::Internal { .. } = opts.enums {
+            bail!(ErrorKind::UnsupportedTaggedVariant(variant))
+        }
         Ok(TupleVariantSerializer {
-            outter_object,
-            inner: ArraySerializer {
-                cx,
-                array: inner_array,
-            },
+            variant,
+            inner: ArraySerializer::new(cx, opts),
         })
     }
 }
@@ -435,7 +802,8 @@ where
 
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self.outter_object.upcast())
+        let ArraySerializer { cx, opts, array } = self.inner;
+        tag_variant(cx, opts, self.variant, array.upcast(), false)
     }
 }
 
@@ -444,14 +812,21 @@ impl<'a, 'j, C> MapSerializer<'a, 'j, C>
 where
     C: Context<'j>,
 {
-    fn new(cx: &'a mut C) -> Self {
-        let object = JsObject::new(cx);
+    fn new(cx: &'a mut C, opts: &'a SerializerOptions) -> LibResult<Self> {
+        let object = match opts.maps {
+            MapMode::Object => opts.new_object(cx, opts.null_prototype_maps)?,
+            MapMode::Map => js::construct_global(cx, "Map", vec![])?,
+            MapMode::Entries => JsArray::new(cx, 0).upcast(),
+        };
         let key_holder = JsObject::new(cx);
-        MapSerializer {
+        Ok(MapSerializer {
             cx,
+            opts,
             object,
             key_holder,
-        }
+            key_is_proto: false,
+            len: 0,
+        })
     }
 }
 
@@ -467,7 +842,13 @@ where
     where
         T: Serialize,
     {
-        let key = to_value(self.cx, key)?;
+        self.key_is_proto = false;
+        let key = match self.opts.maps {
+            MapMode::Object => {
+                key.serialize(MapKeySerializer::new(self.cx, &mut self.key_is_proto))?
+            }
+            MapMode::Map | MapMode::Entries => key.serialize(Serializer::new(self.cx, self.opts))?,
+        };
         self.key_holder.set(self.cx, "key", key)?;
         Ok(())
     }
@@ -477,8 +858,26 @@ where
         T: Serialize,
     {
         let key: Handle<'j, JsValue> = self.key_holder.get(&mut *self.cx, "key")?;
-        let value_obj = to_value(self.cx, value)?;
-        self.object.set(self.cx, key, value_obj)?;
+        let value_obj = value.serialize(Serializer::new(self.cx, self.opts))?;
+        match self.opts.maps {
+            MapMode::Object if self.opts.omit_none && value_obj.is_a::<JsUndefined>() => {}
+            MapMode::Object if self.key_is_proto => {
+                js::define_own_property(self.cx, self.object, key, value_obj)?;
+            }
+            MapMode::Object => {
+                self.object.set(self.cx, key, value_obj)?;
+            }
+            MapMode::Map => {
+                js::call_method(self.cx, self.object, "set", vec![key, value_obj])?;
+            }
+            MapMode::Entries => {
+                let entry = JsArray::new(self.cx, 2);
+                entry.set(self.cx, 0, key)?;
+                entry.set(self.cx, 1, value_obj)?;
+                self.object.set(self.cx, self.len, entry)?;
+                self.len += 1;
+            }
+        }
         Ok(())
     }
 
@@ -488,15 +887,217 @@ where
     }
 }
 
+/// Implements a `serialize_*` method of `MapKeySerializer` that rejects the key
+macro_rules! reject_map_key {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ret:ty = $key_type:expr;)*) => {
+        $(
+            #[inline]
+            fn $method(self, $(_: $ty),*) -> Result<$ret, Self::Error> {
+                bail!(ErrorKind::InvalidMapKey($key_type))
+            }
+        )*
+    };
+}
+
+#[doc(hidden)]
+impl<'a, 'j, C> MapKeySerializer<'a, 'j, C>
+where
+    C: Context<'j>,
+{
+    #[inline]
+    fn new(cx: &'a mut C, is_proto: &'a mut bool) -> Self {
+        MapKeySerializer {
+            cx,
+            is_proto,
+            ph: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn key(self, key: &str) -> LibResult<Handle<'j, JsValue>> {
+        *self.is_proto = key == "__proto__";
+        let js_str = JsString::try_new(self.cx, key)
+            .map_err(|_| ErrorKind::StringTooLong(key.len()))?;
+        Ok(js_str.upcast())
+    }
+}
+
+/// Serializes a map key to a property name, integers are converted
+/// to strings in Rust so they do not lose precision
+#[doc(hidden)]
+impl<'a, 'j, C> ser::Serializer for MapKeySerializer<'a, 'j, C>
+where
+    C: Context<'j>,
+{
+    type Ok = Handle<'j, JsValue>;
+    type Error = Error;
+
+    type SerializeSeq = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        self.key(if v { "true" } else { "false" })
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.key(&v.to_string())
+    }
+
+    /// floats are left for JS to convert so keys match `String(number)`
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(JsNumber::new(self.cx, as_num::<_, f64>(v)?).upcast())
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(JsNumber::new(self.cx, v).upcast())
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        let mut b = [0; 4];
+        self.key(v.encode_utf8(&mut b))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.key(v)
+    }
+
+    #[inline]
+    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.key(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        value.serialize(self)
+    }
+
+    reject_map_key! {
+        serialize_bytes(v: &[u8]) -> Self::Ok = "bytes";
+        serialize_none() -> Self::Ok = "none";
+        serialize_unit() -> Self::Ok = "unit";
+        serialize_unit_struct(name: &'static str) -> Self::Ok = "unit struct";
+        serialize_seq(len: Option<usize>) -> Self::SerializeSeq = "sequence";
+        serialize_tuple(len: usize) -> Self::SerializeTuple = "tuple";
+        serialize_tuple_struct(
+            name: &'static str,
+            len: usize
+        ) -> Self::SerializeTupleStruct = "tuple struct";
+        serialize_tuple_variant(
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> Self::SerializeTupleVariant = "tuple variant";
+        serialize_map(len: Option<usize>) -> Self::SerializeMap = "map";
+        serialize_struct(name: &'static str, len: usize) -> Self::SerializeStruct = "struct";
+        serialize_struct_variant(
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> Self::SerializeStructVariant = "struct variant";
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        bail!(ErrorKind::InvalidMapKey("newtype variant"))
+    }
+}
+
 #[doc(hidden)]
 impl<'a, 'j, C> StructSerializer<'a, 'j, C>
 where
     C: Context<'j>,
 {
     #[inline]
-    fn new(cx: &'a mut C) -> Self {
-        let object = JsObject::new(cx);
-        StructSerializer { cx, object }
+    fn new(cx: &'a mut C, opts: &'a SerializerOptions) -> LibResult<Self> {
+        let object = opts.new_object(cx, opts.null_prototype_structs)?;
+        Ok(StructSerializer { cx, opts, object })
     }
 }
 
@@ -517,8 +1118,17 @@ where
     where
         T: Serialize,
     {
-        let value = to_value(self.cx, value)?;
-        self.object.set(self.cx, key, value)?;
+        let value = value.serialize(Serializer::new(self.cx, self.opts))?;
+        // `None` with `omit_none` or a missing `Patch`
+        if value.is_a::<JsUndefined>() {
+            return Ok(());
+        }
+        if key == "__proto__" {
+            let key = JsString::new(self.cx, key).upcast();
+            js::define_own_property(self.cx, self.object, key, value)?;
+        } else {
+            self.object.set(self.cx, key, value)?;
+        }
         Ok(())
     }
 
@@ -533,16 +1143,10 @@ impl<'a, 'j, C> StructVariantSerializer<'a, 'j, C>
 where
     C: Context<'j>,
 {
-    fn new(cx: &'a mut C, key: &'static str) -> LibResult<Self> {
-        let inner_object = JsObject::new(cx);
-        let outter_object = JsObject::new(cx);
-        outter_object.set(cx, key, inner_object)?;
+    fn new(cx: &'a mut C, opts: &'a SerializerOptions, variant: &'static str) -> LibResult<Self> {
         Ok(StructVariantSerializer {
-            outer_object: outter_object,
-            inner: StructSerializer {
-                cx,
-                object: inner_object,
-            },
+            variant,
+            inner: StructSerializer::new(cx, opts)?,
         })
     }
 }
@@ -570,6 +1174,7 @@ where
 
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self.outer_object.upcast())
+        let StructSerializer { cx, opts, object } = self.inner;
+        tag_variant(cx, opts, self.variant, object.upcast(), false)
     }
 }
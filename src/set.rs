@@ -0,0 +1,59 @@
+//!
+//! Serialize a Rust set into an ES `Set`
+//!
+//! Serde gives no way to tell a set from any other sequence, so sets are
+//! opted in field by field with `#[serde(with = "neon_serde::set")]`.
+//! Other serializers see the field as a plain sequence.
+//!
+//! ```rust,no_run
+//! # #![allow(dead_code)]
+//! extern crate neon_serde;
+//! #[macro_use]
+//! extern crate serde_derive;
+//!
+//! use std::collections::HashSet;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct User {
+//!     #[serde(with = "neon_serde::set")]
+//!     roles: HashSet<String>,
+//!     // still an array
+//!     names: Vec<String>,
+//! }
+//! # fn main () {
+//! # }
+//! ```
+//!
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Name used to recognize a set in `Serializer::serialize_newtype_struct`
+pub(crate) const TOKEN: &str = "$neon_serde::private::Set";
+
+/// Serializes `value` as an ES `Set` when used with `to_value`
+///
+/// # Errors
+///
+/// Any error from serializing the elements of `value`
+///
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(TOKEN, value)
+}
+
+/// Deserializes a set from an ES `Set` or an array
+///
+/// # Errors
+///
+/// Any error from deserializing `T`
+///
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer)
+}
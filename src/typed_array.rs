@@ -0,0 +1,217 @@
+//!
+//! Convert numeric vectors to and from JS typed arrays
+//!
+//! `TypedArray<f64>` is serialized to a `Float64Array` with a single copy
+//! of the vector's memory instead of building an array one `Number` at a time.
+//! It is deserialized from a `Float64Array` the same way, other typed arrays
+//! are an error. Other serializers see the elements as bytes in native byte order.
+//!
+//! Plain vectors, e.g. `Vec<i32>`, can also be deserialized from any typed array,
+//! each element is checked against the target type.
+//!
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+/// Prefix of the names used to recognize a typed array in `Serializer::serialize_newtype_struct`
+pub(crate) const TOKEN_PREFIX: &str = "$neon_serde::private::TypedArray::";
+
+mod private {
+    /// Only primitive numbers can be viewed as bytes, so `Element` can not be implemented outside
+    /// of this crate
+    pub trait Sealed {}
+}
+
+/// A number type that can be the element of a JS typed array
+pub trait Element: Copy + for<'de> Deserialize<'de> + private::Sealed {
+    /// Name used to recognize the typed array, `TOKEN_PREFIX` followed by `CLASS`
+    #[doc(hidden)]
+    const TOKEN: &'static str;
+    /// The JS typed array class, e.g. `Float64Array`
+    const CLASS: &'static str;
+}
+
+macro_rules! impl_element {
+    ($($ty:ty => $class:tt,)*) => {
+        $(
+            impl private::Sealed for $ty {}
+
+            impl Element for $ty {
+                const TOKEN: &'static str = concat!("$neon_serde::private::TypedArray::", $class);
+                const CLASS: &'static str = $class;
+            }
+        )*
+    };
+}
+
+impl_element! {
+    i8 => "Int8Array",
+    u8 => "Uint8Array",
+    i16 => "Int16Array",
+    u16 => "Uint16Array",
+    i32 => "Int32Array",
+    u32 => "Uint32Array",
+    f32 => "Float32Array",
+    f64 => "Float64Array",
+    i64 => "BigInt64Array",
+    u64 => "BigUint64Array",
+}
+
+/// Gets the typed array class a token passed to `serialize_newtype_struct` stands for
+pub(crate) fn class_for_token(name: &str) -> Option<&str> {
+    if name.starts_with(TOKEN_PREFIX) {
+        Some(&name[TOKEN_PREFIX.len()..])
+    } else {
+        None
+    }
+}
+
+/// Reads elements from bytes in native byte order, trailing bytes that
+/// do not make a whole element are ignored
+pub(crate) fn decode<T: Element>(bytes: &[u8]) -> Vec<T> {
+    let size = mem::size_of::<T>();
+    (0..bytes.len() / size)
+        .map(|i| {
+            // every bit pattern is a valid `Element` and the read is in bounds
+            unsafe { ptr::read_unaligned(bytes[i * size..].as_ptr() as *const T) }
+        })
+        .collect()
+}
+
+/// A vector of numbers that is a JS typed array on the node side,
+/// e.g. `TypedArray<f32>` is a `Float32Array`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TypedArray<T: Element>(pub Vec<T>);
+
+impl<T: Element> From<Vec<T>> for TypedArray<T> {
+    fn from(values: Vec<T>) -> Self {
+        TypedArray(values)
+    }
+}
+
+impl<T: Element> TypedArray<T> {
+    /// The memory of the elements in native byte order, the layout used by JS typed arrays
+    fn as_bytes(&self) -> &[u8] {
+        let len = self.0.len() * mem::size_of::<T>();
+        // every `Element` is a primitive number without padding or invalid bit patterns
+        unsafe { slice::from_raw_parts(self.0.as_ptr() as *const u8, len) }
+    }
+}
+
+/// Serializes a slice with `serialize_bytes`
+struct RawBytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for RawBytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl<T: Element> Serialize for TypedArray<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(T::TOKEN, &RawBytes(self.as_bytes()))
+    }
+}
+
+struct TypedArrayVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Element> Visitor<'de> for TypedArrayVisitor<T> {
+    type Value = TypedArray<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a {}", T::CLASS)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if v.len() % mem::size_of::<T>() != 0 {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        Ok(TypedArray(decode(v)))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(&v)
+    }
+
+    /// a plain array of numbers
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(TypedArray(values))
+    }
+
+    /// other deserializers see the bytes written by `serialize`
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(BytesVisitor(self))
+    }
+}
+
+/// Only accepts bytes, so a sequence is read as bytes instead of elements
+struct BytesVisitor<T>(TypedArrayVisitor<T>);
+
+impl<'de, T: Element> Visitor<'de> for BytesVisitor<T> {
+    type Value = TypedArray<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(formatter)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_bytes(v)
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_bytes(&v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        self.0.visit_bytes(&bytes)
+    }
+}
+
+impl<'de, T: Element> Deserialize<'de> for TypedArray<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(T::TOKEN, TypedArrayVisitor(PhantomData))
+    }
+}
@@ -1,6 +1,9 @@
 const native = require('../native');
 const expect = require('expect');
 
+// `BigInt` is not available before node 10
+const itBigInt = typeof BigInt === 'undefined' ? it.skip : it;
+
 describe('all values ok', () => {
     it('value 32', () => {
         expect(native.make_num_32()).toBe(32);
@@ -56,11 +59,173 @@ describe('all values ok', () => {
         });
     });
 
+    itBigInt('make_bigint_always', () => {
+        expect(native.make_bigint_always()).toEqual([BigInt(1), BigInt('9007199254740993')]);
+    });
+
+    itBigInt('make_bigint_when_unsafe', () => {
+        expect(native.make_bigint_when_unsafe()).toEqual([
+            1,
+            BigInt('-9007199254740993'),
+            BigInt('340282366920938463463374607431768211455'),
+        ]);
+    });
+
+    itBigInt('make_bigint_nested', () => {
+        expect(native.make_bigint_nested()).toEqual({
+            a: BigInt(1),
+            b: BigInt(2),
+            c: [
+                {Newtype: BigInt(3)},
+                {Tuple: [BigInt(4), BigInt(-4)]},
+                {Struct: {a: BigInt(6)}},
+            ],
+            d: {x: [BigInt(5)]},
+        });
+    });
+
+    it('make_es_map', () => {
+        const [numbers, points, strings] = native.make_es_map();
+        expect(numbers).toBeInstanceOf(Map);
+        expect(numbers).toEqual(new Map([[1, 'a'], [2, 'b']]));
+        expect(points).toEqual(new Map([[{x: 1, y: 2}, 'p']]));
+        expect(strings.get('__proto__')).toBe(1);
+    });
+
+    it('make_es_set', () => {
+        const o = native.make_es_set();
+        expect(o.a).toBeInstanceOf(Set);
+        expect(o).toEqual({a: new Set([1, 2]), b: [1, 2], c: [3, 4]});
+    });
+
+    it('make_dates', () => {
+        const o = native.make_dates();
+        expect(o.a).toBeInstanceOf(Date);
+        expect(o).toEqual({
+            a: new Date(1500),
+            b: new Date(-86400000),
+            c: new Date(1000000000000),
+        });
+    });
+
+    it('make_entries_map', () => {
+        expect(native.make_entries_map()).toEqual([[[0, 1], 'a'], [[1, 0], 'b']]);
+    });
+
+    it('make_object_u64_keys', () => {
+        expect(native.make_object_u64_keys()).toEqual({'1': 'b', '18446744073709551615': 'a'});
+    });
+
+    it('make_proto_map', () => {
+        for (const o of [native.make_proto_map(), native.make_proto_struct()]) {
+            expect(Object.getPrototypeOf(o)).toBe(Object.prototype);
+            expect(o.polluted).toBe(undefined);
+            expect(Object.getOwnPropertyNames(o)).toEqual(['__proto__']);
+            expect(Object.getOwnPropertyDescriptor(o, '__proto__').value).toEqual({polluted: true});
+        }
+    });
+
+    it('make_null_proto', () => {
+        const [map, struct] = native.make_null_proto();
+        expect(Object.getPrototypeOf(map)).toBe(null);
+        expect(Object.getPrototypeOf(struct)).toBe(null);
+        expect(map.polluted).toBe(undefined);
+        expect(Object.getOwnPropertyNames(map)).toEqual(['__proto__']);
+        expect(struct.c).toBe('c');
+    });
+
+    it('make_typed_arrays', () => {
+        const [f64, f32, i32, u16, u8] = native.make_typed_arrays();
+        expect(f64).toEqual(new Float64Array([0.5, -1.25, 1e300]));
+        expect(f32).toEqual(new Float32Array([1.5, -2]));
+        expect(i32).toEqual(new Int32Array([-1, 0, 2147483647]));
+        expect(u16).toEqual(new Uint16Array([0, 65535]));
+        expect(u8).toEqual(new Uint8Array([]));
+    });
+
+    itBigInt('make_bigint_typed_arrays', () => {
+        const [i64, u64] = native.make_bigint_typed_arrays();
+        expect(i64).toEqual(new BigInt64Array([BigInt(-5)]));
+        expect(u64).toEqual(new BigUint64Array([BigInt('18446744073709551615')]));
+    });
+
+    it('make_internally_tagged', () => {
+        expect(native.make_internally_tagged()).toEqual([
+            {type: 'Empty'},
+            {type: 'Circle', x: 1, y: 2},
+            {type: 'Rect', w: 3, h: 4},
+        ]);
+    });
+
+    it('make_internally_tagged_raw', () => {
+        const payload = {a: 1};
+        expect(native.make_internally_tagged_raw(payload)).toEqual({type: 'Raw', a: 1});
+        expect(payload).toEqual({a: 1});
+        const bare = Object.create(null);
+        bare.a = 1;
+        expect(Object.getPrototypeOf(native.make_internally_tagged_raw(bare))).toBe(null);
+        expect(Object.keys(bare)).toEqual(['a']);
+    });
+
+    it('make_internally_tagged_null_prototype', () => {
+        const o = native.make_internally_tagged_null_prototype();
+        expect(Object.getPrototypeOf(o)).toBe(null);
+        expect(Object.assign({}, o)).toEqual({a: 1, type: 'Dict'});
+    });
+
+    it('make_adjacently_tagged', () => {
+        expect(native.make_adjacently_tagged()).toEqual([
+            {t: 'Empty'},
+            {t: 'Circle', c: {x: 1, y: 2}},
+            {t: 'Rect', c: {w: 3, h: 4}},
+            {t: 'Pair', c: [5, 6]},
+            {t: 'Radius', c: 7},
+        ]);
+    });
+
+    it('make_untagged', () => {
+        expect(native.make_untagged()).toEqual([null, {x: 1, y: 2}, {w: 3, h: 4}, [5, 6], 7]);
+    });
+
+    it('make_unit_variant_index', () => {
+        expect(native.make_unit_variant_index()).toEqual([[0, 1, 2], {Tuple: [1, 'a']}]);
+    });
+
+    it('make_omit_none', () => {
+        const o = native.make_omit_none();
+        expect(Object.keys(o)).toEqual(['a', 'c', 'd', 'e']);
+        expect(o.c).toEqual([2, undefined]);
+        expect(o.c.length).toBe(2);
+        expect(o.d).toEqual({y: 3});
+        expect(Object.keys(o.d)).toEqual(['y']);
+        expect(o.e).toBe(null);
+    });
+
+    it('make_strict_safe_integer', () => {
+        expect(native.make_strict_safe_integer()).toEqual([9007199254740991, -9007199254740991]);
+    });
+
+    it('expect_strict_u64', () => {
+        expect(native.expect_strict_u64(9007199254740991)).toBe(9007199254740991);
+    });
+
     it('make_buff', () => {
         const buff = new Buffer([255, 254, 253]);
         expect(native.make_buff()).toEqual(buff);
     });
 
+    it('make_uint8_array', () => {
+        const bytes = native.make_uint8_array();
+        expect(Buffer.isBuffer(bytes)).toBe(false);
+        expect(bytes).toEqual(new Uint8Array([255, 254, 253]));
+    });
+
+    it('make_array_buffer', () => {
+        const bytes = native.make_array_buffer();
+        expect(bytes).toBeInstanceOf(ArrayBuffer);
+        expect(new Uint8Array(bytes)).toEqual(new Uint8Array([255, 254, 253]));
+    });
+
     it('expect_hello_world', () => {
         native.expect_hello_world("hello world");
     });
@@ -106,6 +271,165 @@ describe('all values ok', () => {
         }
     });
 
+    it('expect_buffer other byte containers', () => {
+        native.expect_buffer(new Uint8Array([252, 251, 250]).buffer);
+
+        const backing = new Uint8Array([0, 252, 251, 250, 0]).buffer;
+        native.expect_buffer(new Uint8Array(backing, 1, 3));
+        native.expect_buffer(new DataView(backing, 1, 3));
+        native.expect_buffer(new Int8Array(backing, 1, 3));
+
+        const wide = new Uint8Array([0, 0, 252, 251, 250, 0]).buffer;
+        const halves = new Uint16Array(wide, 2, 1);
+        expect(() => native.expect_buffer(halves)).toThrow(/assertion failed:/);
+        native.expect_buffer(new DataView(wide, 2, 3));
+    });
+
+    itBigInt('expect_bigint', () => {
+        native.expect_bigint([
+            BigInt('9007199254740993'),
+            BigInt('-9007199254740993'),
+            BigInt('340282366920938463463374607431768211455'),
+            7,
+        ]);
+    });
+
+    it('expect_es_map', () => {
+        native.expect_es_map(new Map([[1, 'a'], [2, 'b']]));
+        // classes are tested with instanceof, not with their Symbol.toStringTag
+        class Tagged extends Map {
+            get [Symbol.toStringTag]() {
+                return 'Tagged';
+            }
+        }
+        native.expect_es_map(new Tagged([[1, 'a'], [2, 'b']]));
+        native.expect_es_map({1: 'a', 2: 'b', [Symbol.toStringTag]: 'Map'});
+    });
+
+    it('expect_es_set', () => {
+        native.expect_es_set([new Set(['b', 'a']), new Set([3, 1, 2])]);
+    });
+
+    it('expect_entries_map', () => {
+        native.expect_entries_map([[[0, 1], 'a'], [[1, 0], 'b']]);
+        native.expect_entries_map(native.make_entries_map());
+        native.expect_entries_map(new Map([[[0, 1], 'a'], [[1, 0], 'b']]));
+    });
+
+    it('expect_typed_keys', () => {
+        native.expect_typed_keys({
+            a: {'1': 'a', '20': 'b'},
+            b: {'-5': true},
+            c: {'true': 1, 'false': 0},
+            d: {'x': 1},
+            e: {'A': 1, 'B': 2},
+            f: {'1': 1},
+        });
+        native.expect_typed_keys(native.make_typed_keys());
+    });
+
+    it('expect_typed_vectors', () => {
+        native.expect_typed_vectors([
+            new Float64Array([0.5, -1.25]),
+            new Int32Array([-1, 7]),
+            new Uint8ClampedArray([200]),
+            new Int8Array([3]),
+        ]);
+        const backing = new Float64Array([9, 0.5, -1.25, 9]).buffer;
+        native.expect_typed_vectors([
+            new Float64Array(backing, 8, 2),
+            [-1, 7],
+            [200],
+            new Float32Array([3]),
+        ]);
+        native.expect_typed_vectors([[0.5, -1.25], [-1, 7], new Uint8Array([200]), [3]]);
+        native.expect_typed_vectors([[0.5, -1.25], [-1, 7], Buffer.from([200]), [3]]);
+    });
+
+    it('expect_any_bytes', () => {
+        const backing = new Uint8Array([0, 252, 251, 250, 0]).buffer;
+        native.expect_any_bytes(new DataView(backing, 1, 3));
+        native.expect_any_bytes(new Uint8Array(backing, 1, 3));
+        native.expect_any_bytes(Buffer.from([252, 251, 250]));
+        native.expect_any_bytes(new Uint8Array([252, 251, 250]).buffer);
+    });
+
+    it('expect_typed_array', () => {
+        native.expect_typed_array(new Float64Array([0.5, -1.25]));
+        native.expect_typed_array([0.5, -1.25]);
+    });
+
+    it('rt_typed_arrays', () => {
+        const arrays = native.make_typed_arrays();
+        expect(native.roundtrip_typed_arrays(arrays)).toEqual(arrays);
+    });
+
+    itBigInt('rt_bigint_typed_arrays', () => {
+        const arrays = native.make_bigint_typed_arrays();
+        expect(native.roundtrip_bigint_typed_arrays(arrays)).toEqual(arrays);
+    });
+
+    it('rt_raw_value', () => {
+        const payload = {user: 'a', callback: () => 1};
+        const o = native.roundtrip_raw_value({name: 'job', payload});
+        expect(o.name).toBe('JOB');
+        expect(o.payload).toBe(payload);
+    });
+
+    it('rt_raw_value_to_json', () => {
+        const payload = new Date(0);
+        const name = {toJSON: () => 'job'};
+        const o = native.roundtrip_raw_value_to_json({name, payload});
+        expect(o.name).toBe('JOB');
+        expect(o.payload).toBe(payload);
+    });
+
+    it('map_with_callback', () => {
+        expect(native.map_with_callback([5, 7], (v, i) => `${i}:${v}`)).toEqual(['0:5', '1:7']);
+        expect(() => native.map_with_callback([5], () => 1)).toThrow();
+        expect(() => native.map_with_callback([5], 'not a function')).toThrow();
+    });
+
+    it('count_callback_args', () => {
+        expect(native.count_callback_args((...args) => args.length)).toEqual([0, 1, 1]);
+    });
+
+    it('expect_colors', () => {
+        native.expect_colors([2, 0, 1]);
+        native.expect_colors(['Blue', 'Red', 1]);
+    });
+
+    it('expect_update', () => {
+        native.expect_update({a: undefined, b: null, c: 1});
+    });
+
+    it('expect_patch', () => {
+        native.expect_patch({a: undefined, b: null, c: 1});
+    });
+
+    it('rt_patch', () => {
+        const o = native.roundtrip_patch({a: undefined, b: null, c: 1});
+        expect(o).toEqual({b: null, c: 1});
+        expect('a' in o).toBe(false);
+        native.expect_patch(o);
+    });
+
+    it('expect_to_json', () => {
+        const amount = {toJSON: () => 42};
+        const event = {name: 'launch', at: new Date(0), amount};
+        native.expect_to_json(event);
+        native.expect_to_json(JSON.parse(JSON.stringify(event)));
+    });
+
+    it('expect_dates', () => {
+        native.expect_dates({
+            a: new Date(1500),
+            b: '1969-12-31T00:00:00.000Z',
+            c: 1000000000000,
+        });
+        native.expect_dates({a: 1500, b: '1969-12-31', c: '2001-09-09T02:46:40+01:00'});
+    });
+
     it('rt_rust_js_rust', () => {
         const obj = native.make_object();
         native.expect_obj(obj);
@@ -154,6 +478,122 @@ describe('throwing functions', () => {
         expect(() => native.expect_buffer()).toThrow(/not enough arguments/);
     });
 
+    itBigInt('expect_u64 out of range', () => {
+        expect(() => native.expect_u64(BigInt(-1))).toThrow(/invalid value/);
+        expect(() => native.expect_u64(BigInt('18446744073709551616'))).toThrow(/invalid value/);
+        expect(() => native.expect_u64(BigInt('1' + '0'.repeat(40)))).toThrow(/BigInt out of range/);
+    });
+
+    it('make_object_tuple_keys', () => {
+        expect(() => native.make_object_tuple_keys())
+            .toThrow(/Map key must be a string, number, bool, char or unit variant got: tuple/);
+    });
+
+    it('make_internally_tagged_tuple', () => {
+        expect(() => native.make_internally_tagged_tuple())
+            .toThrow(/Internally tagged variant must contain a struct or map: Pair/);
+    });
+
+    it('make_internally_tagged_map', () => {
+        expect(() => native.make_internally_tagged_map())
+            .toThrow(/Internally tagged variant must contain a struct or map: Dict/);
+    });
+
+    it('make_internally_tagged_raw', () => {
+        const disguised = Object.assign(new Date(0), {[Symbol.toStringTag]: 'Object'});
+        for (const content of [new Date(0), new Uint8Array(1), new Set(), disguised]) {
+            expect(() => native.make_internally_tagged_raw(content))
+                .toThrow(/Internally tagged variant must contain a struct or map: Raw/);
+        }
+    });
+
+    it('expect_dates', () => {
+        const dates = b => ({a: 1500, b, c: 1000000000000});
+        // local time or implementation defined formats
+        for (const b of ['1969-12-31T00:00:00', 'Dec 31 1969', '1969-12-31 00:00Z', '']) {
+            expect(() => native.expect_dates(dates(b))).toThrow(/invalid value/);
+        }
+        expect(() => native.expect_dates(dates('1969-13-31'))).toThrow(/invalid value/);
+    });
+
+    it('make_far_date', () => {
+        expect(() => native.make_far_date()).toThrow(/SystemTime out of range for a JS Date/);
+    });
+
+    it('make_strict_unsafe_integer', () => {
+        expect(() => native.make_strict_unsafe_integer())
+            .toThrow(/Integer outside of Number.MAX_SAFE_INTEGER: 9007199254740993/);
+        // exact in a `Number`, but can not be told apart from its neighbours once in JS
+        expect(() => native.make_strict_exact_integer())
+            .toThrow(/Integer outside of Number.MAX_SAFE_INTEGER: 1152921504606846976/);
+    });
+
+    it('expect_strict_u64', () => {
+        expect(() => native.expect_strict_u64(9007199254740992))
+            .toThrow(/Integer outside of Number.MAX_SAFE_INTEGER/);
+        expect(() => native.expect_strict_u64(Infinity)).toThrow(/invalid type: floating point/);
+    });
+
+    it('expect_strict_nested', () => {
+        const big = 9007199254740992;
+        const valid = () => ({
+            a: 1,
+            b: 2,
+            c: [{Newtype: 3}, {Tuple: [4, -4]}, {Struct: {a: 6}}],
+            d: {x: [5]},
+        });
+        expect(native.expect_strict_nested(valid())).toEqual(valid());
+
+        const cases = [
+            o => o.a = big,
+            o => o.b = big,
+            o => o.c[0].Newtype = big,
+            o => o.c[1].Tuple[1] = -big,
+            o => o.c[2].Struct.a = big,
+            o => o.d.x[0] = big,
+        ];
+        for (const mutate of cases) {
+            const o = valid();
+            mutate(o);
+            expect(() => native.expect_strict_nested(o))
+                .toThrow(/Integer outside of Number.MAX_SAFE_INTEGER/);
+        }
+    });
+
+    it('expect_es_map', () => {
+        expect(() => native.expect_es_map(new Map([[1, 'a']]))).toThrow(/assertion failed:/);
+        expect(() => native.expect_es_map(new Map([['x', 'a']]))).toThrow(/invalid type/);
+    });
+
+    it('expect_entries_map', () => {
+        expect(() => native.expect_entries_map([[[0, 1], 'a', 'b']])).toThrow(/invalid length 3/);
+    });
+
+    it('expect_typed_keys', () => {
+        expect(() => native.expect_typed_keys({a: {'x': 'a'}}))
+            .toThrow(/invalid type: string "x", expected u32/);
+    });
+
+    it('expect_typed_vectors', () => {
+        const valid = () => [new Float64Array([0.5, -1.25]), [-1, 7], [200], [3]];
+        const floats = valid();
+        floats[1] = new Float64Array([-1.5, 7]);
+        expect(() => native.expect_typed_vectors(floats)).toThrow(/expected i32/);
+        const overflow = valid();
+        overflow[2] = new Int32Array([300]);
+        expect(() => native.expect_typed_vectors(overflow)).toThrow(/expected u8/);
+    });
+
+    it('expect_typed_array', () => {
+        expect(() => native.expect_typed_array(new Float32Array([0.5, -1.25])))
+            .toThrow(/invalid type: Float32Array, expected Float64Array/);
+    });
+
+    it('expect_colors', () => {
+        expect(() => native.expect_colors([3, 0, 1])).toThrow(/expected a variant index/);
+        expect(() => native.expect_colors([0.5, 0, 1])).toThrow(/expected a variant index/);
+    });
+
     it('getter that throws', () => {
         const obj = {
             a: 1,
@@ -110,9 +110,321 @@ make_test!(make_object, {
     value
 });
 
+macro_rules! make_test_with {
+    ($name:ident, $opts:expr, $val:expr) => {
+        fn $name(cx: FunctionContext) -> JsResult<JsValue> {
+            fn inner(mut cx: FunctionContext) -> neon_serde::errors::Result<Handle<JsValue>> {
+                let opts = $opts;
+                let value = $val;
+
+                let handle = neon_serde::to_value_with(&mut cx, &value, &opts)?;
+                Ok(handle)
+            }
+
+            Ok(inner(cx)?)
+        }
+    };
+}
+
+make_test_with!(
+    make_bigint_always,
+    neon_serde::ser::SerializerOptions::new().bigint(neon_serde::ser::BigIntMode::Always),
+    vec![1u64, 9_007_199_254_740_993]
+);
+make_test_with!(
+    make_bigint_when_unsafe,
+    neon_serde::ser::SerializerOptions::new().bigint(neon_serde::ser::BigIntMode::WhenUnsafe),
+    (1u64, -9_007_199_254_740_993i64, u128::max_value())
+);
+make_test_with!(
+    make_strict_unsafe_integer,
+    neon_serde::ser::SerializerOptions::new().strict_integers(true),
+    9_007_199_254_740_993u64
+);
+make_test_with!(
+    make_strict_safe_integer,
+    neon_serde::ser::SerializerOptions::new().strict_integers(true),
+    (9_007_199_254_740_991u64, -9_007_199_254_740_991i64)
+);
+make_test_with!(
+    make_strict_exact_integer,
+    neon_serde::ser::SerializerOptions::new().strict_integers(true),
+    1u64 << 60
+);
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+enum WideEnum {
+    Newtype(u64),
+    Tuple(u64, i64),
+    Struct { a: u64 },
+}
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct WideObject {
+    a: u64,
+    b: Option<u64>,
+    c: Vec<WideEnum>,
+    d: std::collections::BTreeMap<String, (u64,)>,
+}
+
+make_test_with!(
+    make_bigint_nested,
+    neon_serde::SerializerOptions::new().bigint(neon_serde::ser::BigIntMode::Always),
+    {
+        let mut d = std::collections::BTreeMap::new();
+        d.insert("x".to_string(), (5u64,));
+        WideObject {
+            a: 1,
+            b: Some(2),
+            c: vec![
+                WideEnum::Newtype(3),
+                WideEnum::Tuple(4, -4),
+                WideEnum::Struct { a: 6 },
+            ],
+            d,
+        }
+    }
+);
+
+#[derive(Serialize, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+make_test_with!(
+    make_es_map,
+    neon_serde::SerializerOptions::new().maps(neon_serde::ser::MapMode::Map),
+    {
+        use std::collections::BTreeMap;
+        let mut numbers = BTreeMap::new();
+        numbers.insert(1u32, "a");
+        numbers.insert(2u32, "b");
+        let mut points = BTreeMap::new();
+        points.insert(Point { x: 1, y: 2 }, "p");
+        let mut strings = BTreeMap::new();
+        strings.insert("__proto__", 1);
+        (numbers, points, strings)
+    }
+);
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct WithSets {
+    #[serde(with = "neon_serde::set")]
+    a: std::collections::BTreeSet<u32>,
+    b: std::collections::BTreeSet<u32>,
+    c: Vec<u32>,
+}
+
+make_test!(make_es_set, {
+    let a: std::collections::BTreeSet<u32> = vec![1, 2].into_iter().collect();
+    WithSets {
+        b: a.clone(),
+        a,
+        c: vec![3, 4],
+    }
+});
+
+fn tuple_key_map() -> std::collections::BTreeMap<(u32, u32), String> {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert((0, 1), "a".to_string());
+    map.insert((1, 0), "b".to_string());
+    map
+}
+
+make_test_with!(
+    make_entries_map,
+    neon_serde::SerializerOptions::new().maps(neon_serde::ser::MapMode::Entries),
+    tuple_key_map()
+);
+
+make_test!(make_object_tuple_keys, tuple_key_map());
+make_test!(make_object_u64_keys, {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(u64::max_value(), 'a');
+    map.insert(1, 'b');
+    map
+});
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct ProtoStruct {
+    #[serde(rename = "__proto__")]
+    proto: std::collections::BTreeMap<String, bool>,
+}
+
+type ProtoMap = std::collections::BTreeMap<&'static str, std::collections::BTreeMap<String, bool>>;
+
+fn proto_map() -> ProtoMap {
+    let mut polluted = std::collections::BTreeMap::new();
+    polluted.insert("polluted".to_string(), true);
+    let mut map = std::collections::BTreeMap::new();
+    map.insert("__proto__", polluted);
+    map
+}
+
+make_test!(make_proto_map, proto_map());
+make_test!(make_proto_struct, ProtoStruct {
+    proto: proto_map().remove("__proto__").unwrap(),
+});
+make_test_with!(
+    make_null_proto,
+    neon_serde::SerializerOptions::new()
+        .null_prototype_maps(true)
+        .null_prototype_structs(true),
+    (proto_map(), AnObject { a: 1, b: vec![], c: "c".into() })
+);
+
+make_test!(make_typed_arrays, {
+    use neon_serde::TypedArray;
+    (
+        TypedArray(vec![0.5f64, -1.25, 1e300]),
+        TypedArray(vec![1.5f32, -2.0]),
+        TypedArray(vec![-1i32, 0, i32::max_value()]),
+        TypedArray(vec![0u16, 65535]),
+        TypedArray::<u8>(vec![]),
+    )
+});
+
+make_test!(make_bigint_typed_arrays, {
+    use neon_serde::TypedArray;
+    (TypedArray(vec![-5i64]), TypedArray(vec![u64::max_value()]))
+});
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(Point),
+    Rect { w: u32, h: u32 },
+    Pair(u32, u32),
+    Radius(u32),
+}
+
+fn shapes() -> Vec<Shape> {
+    vec![
+        Shape::Empty,
+        Shape::Circle(Point { x: 1, y: 2 }),
+        Shape::Rect { w: 3, h: 4 },
+    ]
+}
+
+fn shapes_with_tagging(tagging: neon_serde::ser::EnumTagging) -> neon_serde::SerializerOptions {
+    neon_serde::SerializerOptions::new().enums(tagging)
+}
+
+make_test_with!(
+    make_internally_tagged,
+    shapes_with_tagging(neon_serde::ser::EnumTagging::Internal { tag: "type".into() }),
+    shapes()
+);
+make_test_with!(
+    make_adjacently_tagged,
+    shapes_with_tagging(neon_serde::ser::EnumTagging::Adjacent {
+        tag: "t".into(),
+        content: "c".into(),
+    }),
+    {
+        let mut shapes = shapes();
+        shapes.push(Shape::Pair(5, 6));
+        shapes.push(Shape::Radius(7));
+        shapes
+    }
+);
+make_test_with!(
+    make_untagged,
+    shapes_with_tagging(neon_serde::ser::EnumTagging::Untagged),
+    {
+        let mut shapes = shapes();
+        shapes.push(Shape::Pair(5, 6));
+        shapes.push(Shape::Radius(7));
+        shapes
+    }
+);
+make_test_with!(
+    make_internally_tagged_tuple,
+    shapes_with_tagging(neon_serde::ser::EnumTagging::Internal { tag: "type".into() }),
+    Shape::Pair(5, 6)
+);
+
+#[derive(Serialize)]
+enum Wrapped<'j> {
+    Dict(std::collections::BTreeMap<String, u32>),
+    Raw(neon_serde::RawValue<'j>),
+}
+
+make_test_with!(
+    make_internally_tagged_map,
+    shapes_with_tagging(neon_serde::ser::EnumTagging::Internal { tag: "type".into() })
+        .maps(neon_serde::ser::MapMode::Map),
+    Wrapped::Dict(std::collections::BTreeMap::new())
+);
+
+make_test_with!(
+    make_internally_tagged_null_prototype,
+    shapes_with_tagging(neon_serde::ser::EnumTagging::Internal { tag: "type".into() })
+        .null_prototype_maps(true),
+    Wrapped::Dict(vec![("a".to_owned(), 1)].into_iter().collect())
+);
+
+fn make_internally_tagged_raw(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+    let opts = shapes_with_tagging(neon_serde::ser::EnumTagging::Internal { tag: "type".into() });
+
+    let value = Wrapped::Raw(neon_serde::RawValue(arg0));
+    let handle = neon_serde::to_value_with(&mut cx, &value, &opts)?;
+    Ok(handle)
+}
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+make_test_with!(
+    make_unit_variant_index,
+    neon_serde::SerializerOptions::new().unit_variant_index(true),
+    (vec![Color::Red, Color::Green, Color::Blue], TypeEnum::Tuple(1, "a".into()))
+);
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct Optionals {
+    a: Option<u32>,
+    b: Option<u32>,
+    c: Vec<Option<u32>>,
+    d: std::collections::BTreeMap<String, Option<u32>>,
+    e: (),
+}
+
+fn optionals() -> Optionals {
+    Optionals {
+        a: Some(1),
+        b: None,
+        c: vec![Some(2), None],
+        d: vec![("x".to_string(), None), ("y".to_string(), Some(3))].into_iter().collect(),
+        e: (),
+    }
+}
+
+make_test_with!(
+    make_omit_none,
+    neon_serde::SerializerOptions::new().omit_none(true),
+    optionals()
+);
+
 const NUMBER_BYTES: &'static [u8] = &[255u8, 254, 253];
 
 make_test!(make_buff, { serde_bytes::Bytes::new(NUMBER_BYTES) });
+make_test_with!(
+    make_uint8_array,
+    neon_serde::SerializerOptions::new().bytes(neon_serde::ser::BytesMode::Uint8Array),
+    serde_bytes::Bytes::new(NUMBER_BYTES)
+);
+make_test_with!(
+    make_array_buffer,
+    neon_serde::SerializerOptions::new().bytes(neon_serde::ser::BytesMode::ArrayBuffer),
+    serde_bytes::Bytes::new(NUMBER_BYTES)
+);
 
 macro_rules! make_expect {
     ($name:ident, $val:expr, $val_type:ty) => {
@@ -167,6 +479,304 @@ make_expect!(
     serde_bytes::ByteBuf
 );
 
+make_expect!(
+    expect_bigint,
+    (9_007_199_254_740_993u64, -9_007_199_254_740_993i64, u128::max_value(), 7u64),
+    (u64, i64, u128, u64)
+);
+
+make_expect!(expect_u64, 0u64, u64);
+
+fn expect_strict_u64(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+    let opts = neon_serde::de::DeserializerOptions::new().strict_integers(true);
+
+    let de_serialized: u64 = neon_serde::from_value_with(&mut cx, arg0, &opts)?;
+    let handle = neon_serde::to_value(&mut cx, &de_serialized)?;
+    Ok(handle)
+}
+
+fn expect_strict_nested(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+    let opts = neon_serde::DeserializerOptions::new().strict_integers(true);
+
+    let de_serialized: WideObject = neon_serde::from_value_with(&mut cx, arg0, &opts)?;
+    let handle = neon_serde::to_value(&mut cx, &de_serialized)?;
+    Ok(handle)
+}
+
+make_expect!(
+    expect_es_map,
+    {
+        use std::collections::HashMap;
+        let mut map = HashMap::new();
+        map.insert(1u32, "a".to_string());
+        map.insert(2u32, "b".to_string());
+        map
+    },
+    std::collections::HashMap<u32, String>
+);
+
+make_expect!(
+    expect_es_set,
+    {
+        use std::collections::BTreeSet;
+        let mut set = BTreeSet::new();
+        set.insert("a".to_string());
+        set.insert("b".to_string());
+        (set, vec![3u8, 1, 2])
+    },
+    (std::collections::BTreeSet<String>, Vec<u8>)
+);
+
+make_expect!(
+    expect_entries_map,
+    tuple_key_map(),
+    std::collections::BTreeMap<(u32, u32), String>
+);
+
+#[derive(Serialize, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+enum KeyEnum {
+    A,
+    B,
+}
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct TypedKeys {
+    a: std::collections::BTreeMap<u32, String>,
+    b: std::collections::BTreeMap<i64, bool>,
+    c: std::collections::BTreeMap<bool, u8>,
+    d: std::collections::BTreeMap<char, u8>,
+    e: std::collections::BTreeMap<KeyEnum, u8>,
+    f: std::collections::BTreeMap<String, u8>,
+}
+
+fn typed_keys() -> TypedKeys {
+    TypedKeys {
+        a: vec![(1, "a".to_string()), (20, "b".to_string())].into_iter().collect(),
+        b: vec![(-5, true)].into_iter().collect(),
+        c: vec![(true, 1), (false, 0)].into_iter().collect(),
+        d: vec![('x', 1)].into_iter().collect(),
+        e: vec![(KeyEnum::A, 1), (KeyEnum::B, 2)].into_iter().collect(),
+        f: vec![("1".to_string(), 1)].into_iter().collect(),
+    }
+}
+
+make_test!(make_typed_keys, typed_keys());
+make_expect!(expect_typed_keys, typed_keys(), TypedKeys);
+
+make_expect!(
+    expect_typed_vectors,
+    (vec![0.5f64, -1.25], vec![-1i32, 7], vec![200u8], vec![3.0f64]),
+    (Vec<f64>, Vec<i32>, Vec<u8>, Vec<f64>)
+);
+
+/// Bytes reached through `deserialize_any` must not be visited as a seq of `u8`
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum Payload {
+    Numbers(Vec<u16>),
+    Bytes(serde_bytes::ByteBuf),
+    Text(String),
+}
+
+make_expect!(
+    expect_any_bytes,
+    Payload::Bytes(serde_bytes::ByteBuf::from(vec![252u8, 251, 250])),
+    Payload
+);
+
+make_expect!(
+    expect_typed_array,
+    neon_serde::TypedArray(vec![0.5f64, -1.25]),
+    neon_serde::TypedArray<f64>
+);
+
+fn roundtrip_typed_arrays(mut cx: FunctionContext) -> JsResult<JsValue> {
+    type Arrays = (
+        neon_serde::TypedArray<f64>,
+        neon_serde::TypedArray<f32>,
+        neon_serde::TypedArray<i32>,
+        neon_serde::TypedArray<u16>,
+        neon_serde::TypedArray<u8>,
+    );
+    let arg0 = cx.argument::<JsValue>(0)?;
+
+    let de_serialized: Arrays = neon_serde::from_value(&mut cx, arg0)?;
+    let handle = neon_serde::to_value(&mut cx, &de_serialized)?;
+    Ok(handle)
+}
+
+fn roundtrip_bigint_typed_arrays(mut cx: FunctionContext) -> JsResult<JsValue> {
+    type Arrays = (neon_serde::TypedArray<i64>, neon_serde::TypedArray<u64>);
+    let arg0 = cx.argument::<JsValue>(0)?;
+
+    let de_serialized: Arrays = neon_serde::from_value(&mut cx, arg0)?;
+    let handle = neon_serde::to_value(&mut cx, &de_serialized)?;
+    Ok(handle)
+}
+
+make_expect!(expect_colors, vec![Color::Blue, Color::Red, Color::Green], Vec<Color>);
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct Update {
+    a: Option<Option<u32>>,
+    b: Option<Option<u32>>,
+    c: Option<Option<u32>>,
+    #[serde(default)]
+    d: Option<Option<u32>>,
+}
+
+fn expect_update(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+    let opts = neon_serde::DeserializerOptions::new().distinguish_null(true);
+
+    let de_serialized: Update = neon_serde::from_value_with(&mut cx, arg0, &opts)?;
+    assert_eq!(
+        de_serialized,
+        Update {
+            a: None,
+            b: Some(None),
+            c: Some(Some(1)),
+            d: None,
+        }
+    );
+    Ok(JsUndefined::new().upcast())
+}
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct PatchUpdate {
+    #[serde(default)]
+    a: neon_serde::Patch<u32>,
+    #[serde(default)]
+    b: neon_serde::Patch<u32>,
+    #[serde(default)]
+    c: neon_serde::Patch<u32>,
+    #[serde(default)]
+    d: neon_serde::Patch<u32>,
+}
+
+make_expect!(
+    expect_patch,
+    PatchUpdate {
+        a: neon_serde::Patch::Missing,
+        b: neon_serde::Patch::Null,
+        c: neon_serde::Patch::Value(1),
+        d: neon_serde::Patch::Missing,
+    },
+    PatchUpdate
+);
+
+fn roundtrip_patch(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+
+    let de_serialized: PatchUpdate = neon_serde::from_value(&mut cx, arg0)?;
+    let handle = neon_serde::to_value(&mut cx, &de_serialized)?;
+    Ok(handle)
+}
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct Event {
+    name: String,
+    at: String,
+    amount: u32,
+}
+
+fn expect_to_json(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+    let opts = neon_serde::DeserializerOptions::new().call_to_json(true);
+
+    let de_serialized: Event = neon_serde::from_value_with(&mut cx, arg0, &opts)?;
+    assert_eq!(
+        de_serialized,
+        Event {
+            name: "launch".into(),
+            at: "1970-01-01T00:00:00.000Z".into(),
+            amount: 42,
+        }
+    );
+    Ok(JsUndefined::new().upcast())
+}
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+struct Dates {
+    a: neon_serde::Date,
+    #[serde(with = "neon_serde::date")]
+    b: std::time::SystemTime,
+    c: neon_serde::Date,
+}
+
+fn dates() -> Dates {
+    use std::time::{Duration, UNIX_EPOCH};
+    Dates {
+        a: neon_serde::Date(UNIX_EPOCH + Duration::from_millis(1_500)),
+        b: UNIX_EPOCH - Duration::from_millis(86_400_000),
+        c: neon_serde::Date(UNIX_EPOCH + Duration::from_millis(1_000_000_000_000)),
+    }
+}
+
+make_test!(make_dates, dates());
+make_expect!(expect_dates, dates(), Dates);
+make_test!(
+    make_far_date,
+    neon_serde::Date(std::time::UNIX_EPOCH + std::time::Duration::from_secs(10_000_000_000_000))
+);
+
+#[derive(Serialize, Deserialize)]
+struct Job<'j> {
+    name: String,
+    #[serde(borrow)]
+    payload: neon_serde::RawValue<'j>,
+}
+
+fn roundtrip_raw_value(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+
+    let mut job: Job = neon_serde::from_value(&mut cx, arg0)?;
+    job.name = job.name.to_uppercase();
+    let handle = neon_serde::to_value(&mut cx, &job)?;
+    Ok(handle)
+}
+
+fn roundtrip_raw_value_to_json(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+    let opts = neon_serde::DeserializerOptions::new().call_to_json(true);
+
+    let mut job: Job = neon_serde::from_value_with(&mut cx, arg0, &opts)?;
+    job.name = job.name.to_uppercase();
+    let handle = neon_serde::to_value(&mut cx, &job)?;
+    Ok(handle)
+}
+
+fn map_with_callback(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+    let arg1 = cx.argument::<JsValue>(1)?;
+
+    let values: Vec<u32> = neon_serde::from_value(&mut cx, arg0)?;
+    let label: neon_serde::Callback<(u32, usize), String> = neon_serde::from_value(&mut cx, arg1)?;
+    let mut labels = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        labels.push(label.call(&mut cx, &(value, i))?);
+    }
+    let handle = neon_serde::to_value(&mut cx, &labels)?;
+    Ok(handle)
+}
+
+fn count_callback_args(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let arg0 = cx.argument::<JsValue>(0)?;
+
+    let count: neon_serde::Callback<(), usize> = neon_serde::from_value(&mut cx, arg0)?;
+    let count_array: neon_serde::Callback<(Vec<u32>,), usize> = neon_serde::from_value(&mut cx, arg0)?;
+    let count_none: neon_serde::Callback<(Option<u32>,), usize> = neon_serde::from_value(&mut cx, arg0)?;
+    let counts = (
+        count.call(&mut cx, &())?,
+        count_array.call(&mut cx, &(vec![1, 2, 3],))?,
+        count_none.call(&mut cx, &(None,))?,
+    );
+    let handle = neon_serde::to_value(&mut cx, &counts)?;
+    Ok(handle)
+}
+
 fn roundtrip_object(mut cx: FunctionContext) -> JsResult<JsValue> {
     let arg0 = cx.argument::<JsValue>(0)?;
 
@@ -181,14 +791,70 @@ register_module!(mut m, {
     m.export_function("make_str_hello", make_str_hello)?;
     m.export_function("make_num_array", make_num_array)?;
     m.export_function("make_buff", make_buff)?;
+    m.export_function("make_uint8_array", make_uint8_array)?;
+    m.export_function("make_array_buffer", make_array_buffer)?;
     m.export_function("make_obj", make_obj)?;
     m.export_function("make_object", make_object)?;
     m.export_function("make_map", make_map)?;
+    m.export_function("make_bigint_always", make_bigint_always)?;
+    m.export_function("make_bigint_when_unsafe", make_bigint_when_unsafe)?;
+    m.export_function("make_bigint_nested", make_bigint_nested)?;
+    m.export_function("make_es_map", make_es_map)?;
+    m.export_function("make_es_set", make_es_set)?;
+    m.export_function("make_entries_map", make_entries_map)?;
+    m.export_function("make_object_tuple_keys", make_object_tuple_keys)?;
+    m.export_function("make_object_u64_keys", make_object_u64_keys)?;
+    m.export_function("make_proto_map", make_proto_map)?;
+    m.export_function("make_proto_struct", make_proto_struct)?;
+    m.export_function("make_null_proto", make_null_proto)?;
+    m.export_function("make_typed_arrays", make_typed_arrays)?;
+    m.export_function("make_bigint_typed_arrays", make_bigint_typed_arrays)?;
+    m.export_function("make_internally_tagged", make_internally_tagged)?;
+    m.export_function("make_adjacently_tagged", make_adjacently_tagged)?;
+    m.export_function("make_untagged", make_untagged)?;
+    m.export_function("make_unit_variant_index", make_unit_variant_index)?;
+    m.export_function("make_omit_none", make_omit_none)?;
+    m.export_function("make_dates", make_dates)?;
+    m.export_function("make_far_date", make_far_date)?;
+    m.export_function("make_internally_tagged_tuple", make_internally_tagged_tuple)?;
+    m.export_function("make_internally_tagged_map", make_internally_tagged_map)?;
+    m.export_function("make_internally_tagged_raw", make_internally_tagged_raw)?;
+    m.export_function(
+        "make_internally_tagged_null_prototype",
+        make_internally_tagged_null_prototype,
+    )?;
+    m.export_function("make_strict_unsafe_integer", make_strict_unsafe_integer)?;
+    m.export_function("make_strict_safe_integer", make_strict_safe_integer)?;
+    m.export_function("make_strict_exact_integer", make_strict_exact_integer)?;
 
     m.export_function("expect_hello_world", expect_hello_world)?;
     m.export_function("expect_obj", expect_obj)?;
     m.export_function("expect_num_array", expect_num_array)?;
     m.export_function("expect_buffer", expect_buffer)?;
+    m.export_function("expect_bigint", expect_bigint)?;
+    m.export_function("expect_u64", expect_u64)?;
+    m.export_function("expect_strict_u64", expect_strict_u64)?;
+    m.export_function("expect_strict_nested", expect_strict_nested)?;
+    m.export_function("expect_es_map", expect_es_map)?;
+    m.export_function("expect_es_set", expect_es_set)?;
+    m.export_function("expect_entries_map", expect_entries_map)?;
+    m.export_function("make_typed_keys", make_typed_keys)?;
+    m.export_function("expect_typed_keys", expect_typed_keys)?;
+    m.export_function("expect_typed_vectors", expect_typed_vectors)?;
+    m.export_function("expect_typed_array", expect_typed_array)?;
+    m.export_function("expect_any_bytes", expect_any_bytes)?;
+    m.export_function("expect_colors", expect_colors)?;
+    m.export_function("expect_update", expect_update)?;
+    m.export_function("expect_patch", expect_patch)?;
+    m.export_function("roundtrip_patch", roundtrip_patch)?;
+    m.export_function("expect_to_json", expect_to_json)?;
+    m.export_function("expect_dates", expect_dates)?;
+    m.export_function("roundtrip_typed_arrays", roundtrip_typed_arrays)?;
+    m.export_function("roundtrip_bigint_typed_arrays", roundtrip_bigint_typed_arrays)?;
+    m.export_function("roundtrip_raw_value", roundtrip_raw_value)?;
+    m.export_function("roundtrip_raw_value_to_json", roundtrip_raw_value_to_json)?;
+    m.export_function("map_with_callback", map_with_callback)?;
+    m.export_function("count_callback_args", count_callback_args)?;
 
     m.export_function("roundtrip_object", roundtrip_object)?;
     Ok(())